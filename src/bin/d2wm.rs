@@ -0,0 +1,210 @@
+//! Prints ward events of replays as JSON Lines, CSV or a table. Built with the
+//! `cli` feature: `cargo install --path . --features cli`.
+
+use clap::{Parser, ValueEnum};
+use d2wm_parser::{match_records, parse_files, write_records, ExportFormat, ParseOptions, WardRecord};
+use serde::Serialize;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Jsonl,
+    Csv,
+    Table,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum Team {
+    Radiant,
+    Dire,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum WardType {
+    Observer,
+    Sentry,
+}
+
+#[derive(Parser)]
+#[command(name = "d2wm", version, about = "Prints ward events of Dota 2 replays")]
+struct Args {
+    /// Replay files, directories holding replays or glob patterns.
+    #[arg(required = true)]
+    paths: Vec<String>,
+    #[arg(short, long, value_enum, default_value = "table")]
+    format: Format,
+    /// Only wards placed by this team.
+    #[arg(long, value_enum)]
+    team: Option<Team>,
+    /// Only wards of this type.
+    #[arg(long = "type", value_enum)]
+    ward_type: Option<WardType>,
+    /// Only wards placed at or after this game time, in seconds.
+    #[arg(long, allow_negative_numbers = true)]
+    from: Option<i32>,
+    /// Only wards placed at or before this game time, in seconds.
+    #[arg(long, allow_negative_numbers = true)]
+    to: Option<i32>,
+    /// Keep events parsed before a replay failed instead of skipping it.
+    #[arg(long)]
+    allow_partial: bool,
+    /// Number of replays parsed in parallel, one per CPU by default.
+    #[arg(short, long)]
+    workers: Option<usize>,
+}
+
+impl Args {
+    fn matches(&self, ward: &WardRecord) -> bool {
+        self.team.is_none_or(|team| (team == Team::Radiant) == ward.is_radiant)
+            && self
+                .ward_type
+                .is_none_or(|ward_type| (ward_type == WardType::Observer) == ward.is_obs)
+            && self.from.is_none_or(|from| ward.time_placed >= from)
+            && self.to.is_none_or(|to| ward.time_placed <= to)
+    }
+}
+
+#[derive(Serialize)]
+struct JsonRecord<'a> {
+    match_id: u64,
+    #[serde(flatten)]
+    ward: &'a WardRecord,
+}
+
+fn is_replay(path: &Path) -> bool {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    [".dem", ".dem.bz2", ".dem.zst"]
+        .iter()
+        .any(|extension| name.ends_with(extension))
+}
+
+/// Expands directories into replays they hold and glob patterns into
+/// matching files, sorted by path.
+fn expand(paths: &[String]) -> anyhow::Result<Vec<PathBuf>> {
+    let mut replays = Vec::new();
+    for path in paths {
+        let path_buf = PathBuf::from(path);
+        if path_buf.is_dir() {
+            let mut entries = std::fs::read_dir(&path_buf)?
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<Result<Vec<_>, _>>()?;
+            entries.retain(|entry| entry.is_file() && is_replay(entry));
+            entries.sort();
+            replays.extend(entries);
+        } else if path_buf.exists() {
+            replays.push(path_buf);
+        } else {
+            let mut matches = glob::glob(path)?.collect::<Result<Vec<_>, _>>()?;
+            if matches.is_empty() {
+                anyhow::bail!("{path}: no such file");
+            }
+            matches.sort();
+            replays.extend(matches);
+        }
+    }
+    Ok(replays)
+}
+
+/// `-1:05` style game clock.
+fn clock(seconds: i32) -> String {
+    let sign = if seconds < 0 { "-" } else { "" };
+    format!("{sign}{}:{:02}", seconds.abs() / 60, seconds.abs() % 60)
+}
+
+fn write_table(mut out: impl Write, replays: &[(u64, Vec<WardRecord>)]) -> std::io::Result<()> {
+    writeln!(
+        out,
+        "{:<12} {:>7} {:>8} {:<8} {:<8} {:<9} {:<20} {:<24} {:>8} {:>8}",
+        "match", "placed", "duration", "team", "type", "event", "placed by", "destroyed by", "x", "y"
+    )?;
+    for (match_id, wards) in replays {
+        for ward in wards {
+            let killer = ward.killer.as_ref().map(|killer| killer.name.as_str()).unwrap_or("");
+            writeln!(
+                out,
+                "{:<12} {:>7} {:>8} {:<8} {:<8} {:<9} {:<20} {:<24} {:>8.0} {:>8.0}",
+                match_id,
+                clock(ward.time_placed),
+                clock(ward.duration),
+                if ward.is_radiant { "radiant" } else { "dire" },
+                if ward.is_obs { "observer" } else { "sentry" },
+                ward.event,
+                ward.player_placed_hero_name.as_deref().unwrap_or(""),
+                killer,
+                ward.world_x,
+                ward.world_y,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn write(args: &Args, replays: &[(u64, Vec<WardRecord>)]) -> anyhow::Result<()> {
+    let mut out = BufWriter::new(std::io::stdout());
+    match args.format {
+        Format::Jsonl => {
+            for (match_id, wards) in replays {
+                for ward in wards {
+                    serde_json::to_writer(
+                        &mut out,
+                        &JsonRecord {
+                            match_id: *match_id,
+                            ward,
+                        },
+                    )?;
+                    writeln!(out)?;
+                }
+            }
+        }
+        Format::Csv => write_records(&mut out, ExportFormat::Csv, replays)?,
+        Format::Table => write_table(&mut out, replays)?,
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let paths = match expand(&args.paths) {
+        Ok(paths) => paths,
+        Err(e) => {
+            eprintln!("d2wm: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let results = match parse_files(&paths, args.workers, |path, data| {
+        let options = ParseOptions {
+            allow_partial: args.allow_partial,
+            ..Default::default()
+        };
+        match_records(path, data, options)
+    }) {
+        Ok(results) => results,
+        Err(e) => {
+            eprintln!("d2wm: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut status = ExitCode::SUCCESS;
+    let mut replays = Vec::new();
+    for (path, result) in paths.iter().zip(results) {
+        match result {
+            Ok((match_id, mut wards)) => {
+                wards.retain(|ward| args.matches(ward));
+                replays.push((match_id, wards));
+            }
+            Err(e) => {
+                eprintln!("d2wm: {}: {e}", path.display());
+                status = ExitCode::FAILURE;
+            }
+        }
+    }
+    if let Err(e) = write(&args, &replays) {
+        eprintln!("d2wm: {e}");
+        return ExitCode::FAILURE;
+    }
+    status
+}
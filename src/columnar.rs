@@ -0,0 +1,235 @@
+//! Arrow representation of ward events and advantage samples. Columns follow
+//! [`WardRecord`] and [`AdvantageSample`] fields, `killer` is a struct column
+//! with [`Killer`] fields, economy columns are struct columns with [`Economy`]
+//! fields.
+
+use arrow::array::*;
+use arrow::buffer::NullBuffer;
+use arrow::datatypes::{DataType, Field, Fields};
+use arrow::error::ArrowError;
+use arrow::record_batch::RecordBatch;
+use std::sync::Arc;
+
+use crate::economy::{AdvantageSample, Economy};
+use crate::killer::Killer;
+use crate::WardRecord;
+
+/// Builds a column by mapping every row with `$value`.
+macro_rules! column {
+    ($rows:expr, BooleanArray, |$row:ident| $value:expr) => {
+        Arc::new(BooleanArray::from(
+            $rows.iter().map(|$row| $value).collect::<Vec<bool>>(),
+        )) as ArrayRef
+    };
+    ($rows:expr, $array:ty, |$row:ident| $value:expr) => {
+        Arc::new(<$array>::from_iter_values($rows.iter().map(|$row| $value))) as ArrayRef
+    };
+}
+
+/// Same as [`column`] for `$value` returning `Option`.
+macro_rules! nullable_column {
+    ($rows:expr, $array:ty, |$row:ident| $value:expr) => {
+        Arc::new(<$array>::from_iter($rows.iter().map(|$row| $value))) as ArrayRef
+    };
+}
+
+/// Builds one record batch holding `wards`.
+pub fn record_batch(wards: &[WardRecord]) -> Result<RecordBatch, ArrowError> {
+    let killers = wards.iter().map(|ward| ward.killer.as_ref()).collect::<Vec<_>>();
+    RecordBatch::try_from_iter_with_nullable([
+        ("ward_id", column!(wards, UInt32Array, |w| w.ward_id), false),
+        ("time_placed", column!(wards, Int32Array, |w| w.time_placed), false),
+        ("duration", column!(wards, Int32Array, |w| w.duration), false),
+        (
+            "paused_duration",
+            column!(wards, Int32Array, |w| w.paused_duration),
+            false,
+        ),
+        (
+            "raw_tick_placed",
+            column!(wards, UInt32Array, |w| w.raw_tick_placed),
+            false,
+        ),
+        ("raw_tick", column!(wards, UInt32Array, |w| w.raw_tick), false),
+        ("is_obs", column!(wards, BooleanArray, |w| w.is_obs), false),
+        ("is_radiant", column!(wards, BooleanArray, |w| w.is_radiant), false),
+        ("event", column!(wards, StringArray, |w| &w.event), false),
+        ("is_deny", column!(wards, BooleanArray, |w| w.is_deny), false),
+        ("post_game", column!(wards, BooleanArray, |w| w.post_game), false),
+        ("game_phase", column!(wards, StringArray, |w| &w.game_phase), false),
+        (
+            "player_placed_steam_id",
+            column!(wards, UInt64Array, |w| w.player_placed_steam_id),
+            false,
+        ),
+        (
+            "player_placed_slot",
+            column!(wards, UInt64Array, |w| w.player_placed_slot as u64),
+            false,
+        ),
+        (
+            "player_placed_team",
+            column!(wards, Int32Array, |w| w.player_placed_team),
+            false,
+        ),
+        (
+            "player_placed_hero",
+            nullable_column!(wards, StringArray, |w| w.player_placed_hero.as_ref()),
+            true,
+        ),
+        (
+            "player_placed_hero_name",
+            nullable_column!(wards, StringArray, |w| w.player_placed_hero_name.as_ref()),
+            true,
+        ),
+        (
+            "player_placed_name",
+            nullable_column!(wards, StringArray, |w| w.player_placed_name.as_ref()),
+            true,
+        ),
+        (
+            "player_placed_economy",
+            Arc::new(economy_array(wards, |w| w.player_placed_economy)) as ArrayRef,
+            true,
+        ),
+        (
+            "player_destroyed_steam_id",
+            nullable_column!(wards, UInt64Array, |w| w.player_destroyed_steam_id),
+            true,
+        ),
+        (
+            "player_destroyed_slot",
+            nullable_column!(wards, UInt64Array, |w| w.player_destroyed_slot.map(|slot| slot as u64)),
+            true,
+        ),
+        (
+            "player_destroyed_team",
+            nullable_column!(wards, Int32Array, |w| w.player_destroyed_team),
+            true,
+        ),
+        (
+            "player_destroyed_hero",
+            nullable_column!(wards, StringArray, |w| w.player_destroyed_hero.as_ref()),
+            true,
+        ),
+        (
+            "player_destroyed_hero_name",
+            nullable_column!(wards, StringArray, |w| w.player_destroyed_hero_name.as_ref()),
+            true,
+        ),
+        (
+            "player_destroyed_name",
+            nullable_column!(wards, StringArray, |w| w.player_destroyed_name.as_ref()),
+            true,
+        ),
+        (
+            "player_destroyed_economy",
+            Arc::new(economy_array(wards, |w| w.player_destroyed_economy)) as ArrayRef,
+            true,
+        ),
+        (
+            "npc_killed",
+            nullable_column!(wards, StringArray, |w| w.npc_killed.as_ref()),
+            true,
+        ),
+        ("killer", Arc::new(killer_array(&killers)) as ArrayRef, true),
+        ("x", column!(wards, UInt16Array, |w| w.x), false),
+        ("y", column!(wards, UInt16Array, |w| w.y), false),
+        ("z", column!(wards, UInt16Array, |w| w.z), false),
+        ("vec_x", column!(wards, Float32Array, |w| w.vec_x), false),
+        ("vec_y", column!(wards, Float32Array, |w| w.vec_y), false),
+        ("vec_z", column!(wards, Float32Array, |w| w.vec_z), false),
+        ("world_x", column!(wards, Float32Array, |w| w.world_x), false),
+        ("world_y", column!(wards, Float32Array, |w| w.world_y), false),
+        ("world_z", column!(wards, Float32Array, |w| w.world_z), false),
+        ("minimap_x", column!(wards, Float32Array, |w| w.minimap_x), false),
+        ("minimap_y", column!(wards, Float32Array, |w| w.minimap_y), false),
+        (
+            "radiant_networth",
+            column!(wards, Int32Array, |w| w.radiant_networth),
+            false,
+        ),
+        ("dire_networth", column!(wards, Int32Array, |w| w.dire_networth), false),
+        ("radiant_xp", column!(wards, Int32Array, |w| w.radiant_xp), false),
+        ("dire_xp", column!(wards, Int32Array, |w| w.dire_xp), false),
+    ])
+}
+
+/// Builds one record batch holding advantage `samples`.
+pub fn advantage_batch(samples: &[AdvantageSample]) -> Result<RecordBatch, ArrowError> {
+    RecordBatch::try_from_iter_with_nullable([
+        ("raw_tick", column!(samples, UInt32Array, |s| s.raw_tick), false),
+        ("time", column!(samples, Int32Array, |s| s.time), false),
+        (
+            "radiant_networth",
+            column!(samples, Int32Array, |s| s.radiant_networth),
+            false,
+        ),
+        (
+            "dire_networth",
+            column!(samples, Int32Array, |s| s.dire_networth),
+            false,
+        ),
+        ("radiant_xp", column!(samples, Int32Array, |s| s.radiant_xp), false),
+        ("dire_xp", column!(samples, Int32Array, |s| s.dire_xp), false),
+    ])
+}
+
+/// Killer fields of rows without a killer are filled with defaults and masked
+/// by the struct null buffer.
+fn killer_array(killers: &[Option<&Killer>]) -> StructArray {
+    let string = |name: &str, value: fn(&Killer) -> &str| {
+        let array = StringArray::from_iter_values(killers.iter().map(|k| k.map_or("", value)));
+        (
+            Arc::new(Field::new(name, DataType::Utf8, false)),
+            Arc::new(array) as ArrayRef,
+        )
+    };
+    let (fields, arrays): (Vec<_>, Vec<_>) = [
+        string("kind", |k| &k.kind),
+        string("name", |k| &k.name),
+        string("unit", |k| &k.unit),
+        string("source", |k| &k.source),
+        (
+            Arc::new(Field::new("team", DataType::Int32, true)),
+            nullable_column!(killers, Int32Array, |k| k.and_then(|k| k.team)),
+        ),
+        (
+            Arc::new(Field::new("player_slot", DataType::UInt64, true)),
+            nullable_column!(killers, UInt64Array, |k| k
+                .and_then(|k| k.player_slot)
+                .map(|slot| slot as u64)),
+        ),
+        (
+            Arc::new(Field::new("steam_id", DataType::UInt64, true)),
+            nullable_column!(killers, UInt64Array, |k| k.and_then(|k| k.steam_id)),
+        ),
+    ]
+    .into_iter()
+    .unzip();
+    let nulls = NullBuffer::from_iter(killers.iter().map(Option::is_some));
+    StructArray::new(Fields::from(fields), arrays, Some(nulls))
+}
+
+/// Economy of every ward, rows without one are masked by the struct null
+/// buffer.
+fn economy_array(wards: &[WardRecord], economy: fn(&WardRecord) -> Option<Economy>) -> StructArray {
+    let economies = wards.iter().map(economy).collect::<Vec<_>>();
+    let int = |name: &str, value: fn(&Economy) -> i32| {
+        (
+            Arc::new(Field::new(name, DataType::Int32, false)),
+            column!(economies, Int32Array, |e| e.as_ref().map_or(0, value)),
+        )
+    };
+    let (fields, arrays): (Vec<_>, Vec<_>) = [
+        int("gold", |e| e.gold),
+        int("net_worth", |e| e.net_worth),
+        int("level", |e| e.level),
+        int("xp", |e| e.xp),
+        int("last_hits", |e| e.last_hits),
+    ]
+    .into_iter()
+    .unzip();
+    let nulls = NullBuffer::from_iter(economies.iter().map(Option::is_some));
+    StructArray::new(Fields::from(fields), arrays, Some(nulls))
+}
@@ -0,0 +1,78 @@
+//! Conversions between entity cell coordinates, world coordinates and
+//! normalized minimap positions.
+//!
+//! Entity positions are stored as a cell index (`CBodyComponent.m_cellX`)
+//! plus an offset inside that cell (`CBodyComponent.m_vecX`). A cell is
+//! [`CELL_SIZE`] world units wide and cell `128` starts at the world origin,
+//! so `world = cell * CELL_SIZE + vec - WORLD_OFFSET`.
+//!
+//! Minimap positions are normalized to `0..1` over the playable area
+//! ([`MAP_MIN`]..[`MAP_MAX`] on both axes) with the origin in the bottom-left
+//! (Radiant) corner, same orientation as world coordinates.
+
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+
+pub const CELL_SIZE: f32 = 128.0;
+pub const WORLD_OFFSET: f32 = 16384.0;
+
+pub const MAP_MIN: f32 = -8192.0;
+pub const MAP_MAX: f32 = 8192.0;
+
+/// Converts cell and in-cell offset into a world coordinate.
+#[cfg_attr(feature = "python", pyfunction)]
+pub fn cell_to_world(cell: u16, vec: f32) -> f32 {
+    cell as f32 * CELL_SIZE + vec - WORLD_OFFSET
+}
+
+/// Converts a world coordinate back into cell and in-cell offset.
+#[cfg_attr(feature = "python", pyfunction)]
+pub fn world_to_cell(world: f32) -> (u16, f32) {
+    let position = world + WORLD_OFFSET;
+    let cell = (position / CELL_SIZE).floor();
+    (cell as u16, position - cell * CELL_SIZE)
+}
+
+/// Converts world `x`/`y` into a `0..1` minimap position.
+#[cfg_attr(feature = "python", pyfunction)]
+pub fn world_to_minimap(x: f32, y: f32) -> (f32, f32) {
+    ((x - MAP_MIN) / (MAP_MAX - MAP_MIN), (y - MAP_MIN) / (MAP_MAX - MAP_MIN))
+}
+
+/// Converts a `0..1` minimap position into world `x`/`y`.
+#[cfg_attr(feature = "python", pyfunction)]
+pub fn minimap_to_world(x: f32, y: f32) -> (f32, f32) {
+    (MAP_MIN + x * (MAP_MAX - MAP_MIN), MAP_MIN + y * (MAP_MAX - MAP_MIN))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-3, "{a} != {b}");
+    }
+
+    #[test]
+    fn cell_round_trips_through_world() {
+        for world in [-8192.0, -1234.5, 0.0, 0.25, 127.9, 128.0, 7000.75] {
+            let (cell, vec) = world_to_cell(world);
+            assert!((0.0..CELL_SIZE).contains(&vec));
+            assert_close(cell_to_world(cell, vec), world);
+        }
+        assert_eq!(world_to_cell(0.0), (128, 0.0));
+        assert_close(cell_to_world(128, 64.0), 64.0);
+    }
+
+    #[test]
+    fn minimap_round_trips_through_world() {
+        for (x, y) in [(-8192.0, -8192.0), (0.0, 0.0), (-3000.5, 4500.25), (8192.0, 8192.0)] {
+            let (minimap_x, minimap_y) = world_to_minimap(x, y);
+            let (world_x, world_y) = minimap_to_world(minimap_x, minimap_y);
+            assert_close(world_x, x);
+            assert_close(world_y, y);
+        }
+        assert_eq!(world_to_minimap(MAP_MIN, MAP_MAX), (0.0, 1.0));
+        assert_eq!(world_to_minimap(0.0, 0.0), (0.5, 0.5));
+    }
+}
@@ -0,0 +1,87 @@
+use d2_stampede::prelude::*;
+use d2_stampede_observers::players::*;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Player's economy and level at some tick.
+#[cfg_attr(feature = "python", pyclass(get_all))]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Economy {
+    /// Reliable and unreliable gold.
+    pub gold: i32,
+    pub net_worth: i32,
+    pub level: i32,
+    /// Experience earned so far.
+    pub xp: i32,
+    pub last_hits: i32,
+}
+
+/// Team data entity holding per-player stats of `team`, indexed by team slot.
+fn team_data_class(team: i32) -> Option<&'static str> {
+    match team {
+        2 => Some("CDOTA_DataRadiant"),
+        3 => Some("CDOTA_DataDire"),
+        _ => None,
+    }
+}
+
+impl Economy {
+    /// Current economy of `player`, `None` if their team data or hero entity
+    /// doesn't exist.
+    pub fn new(ctx: &Context, player: &Player) -> Option<Self> {
+        let data = ctx.entities().get_by_class_name(team_data_class(player.team)?).ok()?;
+        let hero = ctx.entities().get_by_handle(player.hero_handle).ok()?;
+        let slot = player.slot;
+        let reliable_gold = try_property!(data, i32, "m_vecDataTeam.{slot:04}.m_iReliableGold")?;
+        let unreliable_gold = try_property!(data, i32, "m_vecDataTeam.{slot:04}.m_iUnreliableGold")?;
+        Some(Economy {
+            gold: reliable_gold + unreliable_gold,
+            net_worth: try_property!(data, i32, "m_vecDataTeam.{slot:04}.m_iNetWorth")?,
+            level: try_property!(hero, i32, "m_iCurrentLevel")?,
+            xp: try_property!(data, i32, "m_vecDataTeam.{slot:04}.m_iTotalEarnedXP")?,
+            last_hits: try_property!(data, i32, "m_vecDataTeam.{slot:04}.m_iLastHitCount")?,
+        })
+    }
+}
+
+/// Net worth and experience summed over all players of `team`.
+pub fn team_totals(ctx: &Context, team: i32) -> anyhow::Result<(i32, i32)> {
+    let class = team_data_class(team).ok_or_else(|| anyhow::anyhow!("Team {team} has no data entity"))?;
+    let data = ctx.entities().get_by_class_name(class)?;
+    Ok((0..5).fold((0, 0), |(net_worth, xp), slot| {
+        (
+            net_worth + try_property!(data, i32, "m_vecDataTeam.{slot:04}.m_iNetWorth").unwrap_or_default(),
+            xp + try_property!(data, i32, "m_vecDataTeam.{slot:04}.m_iTotalEarnedXP").unwrap_or_default(),
+        )
+    }))
+}
+
+/// Team totals at one point of the game, Radiant advantage is the difference
+/// between Radiant and Dire values.
+#[cfg_attr(feature = "python", pyclass(get_all))]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct AdvantageSample {
+    pub raw_tick: u32,
+    /// Seconds since the horn, pauses excluded.
+    pub time: i32,
+    pub radiant_networth: i32,
+    pub dire_networth: i32,
+    pub radiant_xp: i32,
+    pub dire_xp: i32,
+}
+
+impl AdvantageSample {
+    pub fn new(ctx: &Context, time: i32) -> anyhow::Result<Self> {
+        let (radiant_networth, radiant_xp) = team_totals(ctx, 2)?;
+        let (dire_networth, dire_xp) = team_totals(ctx, 3)?;
+        Ok(AdvantageSample {
+            raw_tick: ctx.net_tick(),
+            time,
+            radiant_networth,
+            dire_networth,
+            radiant_xp,
+            dire_xp,
+        })
+    }
+}
@@ -0,0 +1,156 @@
+use d2_stampede::error::{EntityError, ParserError};
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::progress::Interrupt;
+
+#[cfg_attr(feature = "python", pyclass(eq, eq_int))]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ErrorKind {
+    Corrupt,
+    Truncated,
+    UnsupportedVersion,
+    MissingEntity,
+    Panic,
+    Io,
+    Cancelled,
+    Timeout,
+}
+
+/// Replay data failing the parser's magic check.
+#[derive(Debug)]
+pub enum WrongFormat {
+    /// Replay recorded before Dota 2 moved to Source 2.
+    Source1,
+    /// Empty, too short or not a replay at all.
+    NotReplay,
+}
+
+impl WrongFormat {
+    pub fn of(data: &[u8]) -> Self {
+        if data.starts_with(b"PBUFDEM\0") {
+            WrongFormat::Source1
+        } else {
+            WrongFormat::NotReplay
+        }
+    }
+}
+
+impl std::fmt::Display for WrongFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WrongFormat::Source1 => write!(f, "Source 1 replays aren't supported"),
+            WrongFormat::NotReplay => write!(f, "Not a Source 2 replay"),
+        }
+    }
+}
+
+impl std::error::Error for WrongFormat {}
+
+/// Where and why parsing stopped.
+#[cfg_attr(feature = "python", pyclass(get_all))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParseError {
+    pub kind: ErrorKind,
+    pub message: String,
+    /// Raw net tick, unknown if parsing stopped before the first packet.
+    pub tick: Option<u32>,
+    /// Game clock time in seconds, unknown before the game has started.
+    pub game_time: Option<f32>,
+    /// Class of the entity that caused the failure, if known.
+    pub entity_class: Option<String>,
+}
+
+impl ParseError {
+    pub fn new(e: &anyhow::Error) -> Self {
+        let (kind, entity_class) = classify(e);
+        ParseError {
+            kind,
+            message: e.to_string(),
+            tick: None,
+            game_time: None,
+            entity_class,
+        }
+    }
+
+    pub fn panic(message: &str) -> Self {
+        ParseError {
+            kind: ErrorKind::Panic,
+            message: message.to_string(),
+            tick: None,
+            game_time: None,
+            entity_class: None,
+        }
+    }
+
+    /// Error reading replay from `path`. Undecodable compressed data is
+    /// reported as [`ErrorKind::Corrupt`].
+    pub fn io(e: &std::io::Error, path: &Path) -> Self {
+        ParseError {
+            kind: match e.kind() {
+                std::io::ErrorKind::InvalidData => ErrorKind::Corrupt,
+                _ => ErrorKind::Io,
+            },
+            message: format!("{}: {e}", path.display()),
+            tick: None,
+            game_time: None,
+            entity_class: None,
+        }
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.tick {
+            Some(tick) => write!(f, "Error while parsing at tick {tick}: {}", self.message),
+            None => write!(f, "Error while parsing: {}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+fn classify(e: &anyhow::Error) -> (ErrorKind, Option<String>) {
+    if let Some(e) = e.downcast_ref::<ParserError>() {
+        return match e {
+            ParserError::ReplayEncodingError => (ErrorKind::Truncated, None),
+            ParserError::Entity(e) => classify_entity(e),
+            ParserError::ObserverError(e) => classify(e),
+            _ => (ErrorKind::Corrupt, None),
+        };
+    }
+    if let Some(e) = e.downcast_ref::<EntityError>() {
+        return classify_entity(e);
+    }
+    match e.downcast_ref::<WrongFormat>() {
+        Some(WrongFormat::Source1) => return (ErrorKind::UnsupportedVersion, None),
+        Some(WrongFormat::NotReplay) => return (ErrorKind::Corrupt, None),
+        None => {}
+    }
+    match e.downcast_ref::<Interrupt>() {
+        Some(Interrupt::Cancelled(_)) => (ErrorKind::Cancelled, None),
+        Some(Interrupt::TimedOut(_)) => (ErrorKind::Timeout, None),
+        None => (ErrorKind::Corrupt, None),
+    }
+}
+
+fn classify_entity(e: &EntityError) -> (ErrorKind, Option<String>) {
+    match e {
+        EntityError::ClassNameNotFound(class) => (ErrorKind::MissingEntity, Some(class.clone())),
+        EntityError::PropertyNameNotFound(_, class, _) => (ErrorKind::UnsupportedVersion, Some(class.clone())),
+        EntityError::FieldPathNotFound(_) => (ErrorKind::UnsupportedVersion, None),
+        _ => (ErrorKind::MissingEntity, None),
+    }
+}
+
+pub fn panic_message(panic: &Box<dyn std::any::Any + Send>) -> &str {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown panic"
+    }
+}
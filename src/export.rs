@@ -0,0 +1,233 @@
+use arrow::array::{make_array, Array, ArrayRef, StructArray, UInt64Array};
+use arrow::buffer::NullBuffer;
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::error::ArrowError;
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::columnar::record_batch;
+use crate::WardRecord;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Parquet,
+    /// Struct columns such as `killer` are flattened into `<column>_<field>`
+    /// columns.
+    Csv,
+}
+
+impl ExportFormat {
+    /// `parquet` or `csv`, case insensitive.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "parquet" => Some(ExportFormat::Parquet),
+            "csv" => Some(ExportFormat::Csv),
+            _ => None,
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        Self::from_name(path.extension()?.to_str()?)
+    }
+}
+
+/// Prepends `match_id` column to ward events of one replay.
+fn match_batch(match_id: u64, wards: &[WardRecord]) -> Result<RecordBatch, ArrowError> {
+    let batch = record_batch(wards)?;
+    let mut fields = vec![Arc::new(Field::new("match_id", DataType::UInt64, false))];
+    fields.extend(batch.schema().fields().iter().cloned());
+    let mut columns = vec![Arc::new(UInt64Array::from(vec![match_id; wards.len()])) as ArrayRef];
+    columns.extend(batch.columns().iter().cloned());
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)
+}
+
+/// Replaces struct columns with one `<struct>_<field>` column per field, as
+/// CSV has no nested values.
+fn flatten(batch: &RecordBatch) -> Result<RecordBatch, ArrowError> {
+    let mut fields = Vec::new();
+    let mut columns = Vec::new();
+    for (field, column) in batch.schema().fields().iter().zip(batch.columns()) {
+        let Some(column) = column.as_any().downcast_ref::<StructArray>() else {
+            fields.push(field.clone());
+            columns.push(column.clone());
+            continue;
+        };
+        for (child_field, child) in column.fields().iter().zip(column.columns()) {
+            let nulls = NullBuffer::union(column.nulls(), child.nulls());
+            let child = make_array(child.to_data().into_builder().nulls(nulls).build()?);
+            fields.push(Arc::new(Field::new(
+                format!("{}_{}", field.name(), child_field.name()),
+                child_field.data_type().clone(),
+                true,
+            )));
+            columns.push(child);
+        }
+    }
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)
+}
+
+enum Writer<W: Write + Send> {
+    Parquet(Box<ArrowWriter<W>>),
+    Csv(Box<arrow::csv::Writer<W>>),
+}
+
+/// Writes ward events replay by replay into a single table with a leading
+/// `match_id` column, so only one replay needs to be held in memory at a
+/// time.
+pub struct RecordsWriter<W: Write + Send> {
+    writer: Writer<W>,
+}
+
+impl<W: Write + Send> RecordsWriter<W> {
+    pub fn new(writer: W, format: ExportFormat) -> anyhow::Result<Self> {
+        let writer = match format {
+            ExportFormat::Parquet => {
+                let schema = match_batch(0, &[])?.schema();
+                let properties = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+                Writer::Parquet(Box::new(ArrowWriter::try_new(writer, schema, Some(properties))?))
+            }
+            ExportFormat::Csv => Writer::Csv(Box::new(arrow::csv::Writer::new(writer))),
+        };
+        Ok(RecordsWriter { writer })
+    }
+
+    /// Appends ward events of one replay.
+    pub fn write(&mut self, match_id: u64, wards: &[WardRecord]) -> anyhow::Result<()> {
+        let batch = match_batch(match_id, wards)?;
+        match &mut self.writer {
+            Writer::Parquet(writer) => writer.write(&batch)?,
+            Writer::Csv(writer) => writer.write(&flatten(&batch)?)?,
+        }
+        Ok(())
+    }
+
+    /// Flushes buffered rows and, for Parquet, writes the file footer.
+    pub fn finish(self) -> anyhow::Result<()> {
+        match self.writer {
+            Writer::Parquet(writer) => {
+                writer.close()?;
+            }
+            Writer::Csv(writer) => {
+                writer.into_inner().flush()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes ward events of every `(match_id, wards)` replay into `writer` as a
+/// single table with a leading `match_id` column.
+pub fn write_records<W: Write + Send>(
+    writer: W,
+    format: ExportFormat,
+    replays: &[(u64, Vec<WardRecord>)],
+) -> anyhow::Result<()> {
+    let mut writer = RecordsWriter::new(writer, format)?;
+    for (match_id, wards) in replays {
+        writer.write(*match_id, wards)?;
+    }
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Killer;
+
+    fn record(ward_id: u32, killer: Option<Killer>) -> WardRecord {
+        WardRecord {
+            ward_id,
+            time_placed: 10,
+            duration: 20,
+            paused_duration: 0,
+            raw_tick_placed: 300,
+            raw_tick: 900,
+            is_obs: true,
+            is_radiant: true,
+            event: if killer.is_some() { "killed" } else { "expired" }.to_string(),
+            is_deny: false,
+            post_game: false,
+            game_phase: "in_game".to_string(),
+            player_placed_steam_id: 1,
+            player_placed_slot: 0,
+            player_placed_team: 2,
+            player_placed_hero: Some("CDOTA_Unit_Hero_Lion".to_string()),
+            player_placed_hero_name: Some("Lion".to_string()),
+            player_placed_name: Some("support".to_string()),
+            player_placed_economy: None,
+            player_destroyed_steam_id: None,
+            player_destroyed_slot: None,
+            player_destroyed_team: killer.as_ref().and_then(|x| x.team),
+            player_destroyed_hero: None,
+            player_destroyed_hero_name: None,
+            player_destroyed_name: None,
+            player_destroyed_economy: None,
+            npc_killed: killer.as_ref().map(|x| x.unit.clone()),
+            killer,
+            x: 100,
+            y: 140,
+            z: 130,
+            vec_x: 0.0,
+            vec_y: 0.0,
+            vec_z: 0.0,
+            world_x: 0.0,
+            world_y: 0.0,
+            world_z: 0.0,
+            minimap_x: 0.5,
+            minimap_y: 0.5,
+            radiant_networth: 0,
+            dire_networth: 0,
+            radiant_xp: 0,
+            dire_xp: 0,
+        }
+    }
+
+    #[test]
+    fn csv_flattens_struct_columns() {
+        let killer = Killer {
+            kind: "tower".to_string(),
+            name: "Tower".to_string(),
+            unit: "npc_dota_badguys_tower1_mid".to_string(),
+            source: "npc_dota_badguys_tower1_mid".to_string(),
+            team: Some(3),
+            player_slot: None,
+            steam_id: None,
+        };
+        let mut csv = Vec::new();
+        write_records(
+            &mut csv,
+            ExportFormat::Csv,
+            &[(7, vec![record(1, Some(killer)), record(2, None)])],
+        )
+        .unwrap();
+
+        let csv = String::from_utf8(csv).unwrap();
+        let rows = csv
+            .lines()
+            .map(|line| line.split(',').collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let [header, killed, expired] = &rows[..] else {
+            panic!("expected header and two rows: {csv}");
+        };
+        let column = |name: &str| header.iter().position(|x| *x == name).unwrap();
+        assert_eq!(header[..2], ["match_id", "ward_id"]);
+        assert!(!header.contains(&"killer"));
+        assert!(header.contains(&"player_placed_economy_gold"));
+
+        assert_eq!(killed[..2], ["7", "1"]);
+        assert_eq!(killed[column("killer_kind")], "tower");
+        assert_eq!(killed[column("killer_team")], "3");
+        assert_eq!(killed[column("killer_steam_id")], "");
+
+        assert_eq!(expired[..2], ["7", "2"]);
+        for field in ["kind", "name", "unit", "source", "team", "player_slot", "steam_id"] {
+            assert_eq!(expired[column(&format!("killer_{field}"))], "", "killer_{field}");
+        }
+        assert_eq!(expired[column("player_placed_economy_gold")], "");
+    }
+}
@@ -0,0 +1,151 @@
+//! Hero display names.
+
+/// Display names keyed by hero name with the prefix and underscores removed,
+/// which is the same for entity class names (`CDOTA_Unit_Hero_Nevermore`)
+/// and unit names (`npc_dota_hero_nevermore`).
+const HEROES: &[(&str, &str)] = &[
+    ("abaddon", "Abaddon"),
+    ("abyssalunderlord", "Underlord"),
+    ("alchemist", "Alchemist"),
+    ("ancientapparition", "Ancient Apparition"),
+    ("antimage", "Anti-Mage"),
+    ("arcwarden", "Arc Warden"),
+    ("axe", "Axe"),
+    ("bane", "Bane"),
+    ("batrider", "Batrider"),
+    ("beastmaster", "Beastmaster"),
+    ("bloodseeker", "Bloodseeker"),
+    ("bountyhunter", "Bounty Hunter"),
+    ("brewmaster", "Brewmaster"),
+    ("bristleback", "Bristleback"),
+    ("broodmother", "Broodmother"),
+    ("centaur", "Centaur Warrunner"),
+    ("chaosknight", "Chaos Knight"),
+    ("chen", "Chen"),
+    ("clinkz", "Clinkz"),
+    ("crystalmaiden", "Crystal Maiden"),
+    ("darkseer", "Dark Seer"),
+    ("darkwillow", "Dark Willow"),
+    ("dawnbreaker", "Dawnbreaker"),
+    ("dazzle", "Dazzle"),
+    ("deathprophet", "Death Prophet"),
+    ("disruptor", "Disruptor"),
+    ("doombringer", "Doom"),
+    ("dragonknight", "Dragon Knight"),
+    ("drowranger", "Drow Ranger"),
+    ("earthshaker", "Earthshaker"),
+    ("earthspirit", "Earth Spirit"),
+    ("eldertitan", "Elder Titan"),
+    ("emberspirit", "Ember Spirit"),
+    ("enchantress", "Enchantress"),
+    ("enigma", "Enigma"),
+    ("facelessvoid", "Faceless Void"),
+    ("furion", "Nature's Prophet"),
+    ("grimstroke", "Grimstroke"),
+    ("gyrocopter", "Gyrocopter"),
+    ("hoodwink", "Hoodwink"),
+    ("huskar", "Huskar"),
+    ("invoker", "Invoker"),
+    ("jakiro", "Jakiro"),
+    ("juggernaut", "Juggernaut"),
+    ("keeperofthelight", "Keeper of the Light"),
+    ("kez", "Kez"),
+    ("kunkka", "Kunkka"),
+    ("largo", "Largo"),
+    ("legioncommander", "Legion Commander"),
+    ("leshrac", "Leshrac"),
+    ("lich", "Lich"),
+    ("lifestealer", "Lifestealer"),
+    ("lina", "Lina"),
+    ("lion", "Lion"),
+    ("lonedruid", "Lone Druid"),
+    ("luna", "Luna"),
+    ("lycan", "Lycan"),
+    ("magnataur", "Magnus"),
+    ("marci", "Marci"),
+    ("mars", "Mars"),
+    ("medusa", "Medusa"),
+    ("meepo", "Meepo"),
+    ("mirana", "Mirana"),
+    ("monkeyking", "Monkey King"),
+    ("morphling", "Morphling"),
+    ("muerta", "Muerta"),
+    ("nagasiren", "Naga Siren"),
+    ("necrolyte", "Necrophos"),
+    ("nevermore", "Shadow Fiend"),
+    ("nightstalker", "Night Stalker"),
+    ("nyxassassin", "Nyx Assassin"),
+    ("obsidiandestroyer", "Outworld Destroyer"),
+    ("ogremagi", "Ogre Magi"),
+    ("omniknight", "Omniknight"),
+    ("oracle", "Oracle"),
+    ("pangolier", "Pangolier"),
+    ("phantomassassin", "Phantom Assassin"),
+    ("phantomlancer", "Phantom Lancer"),
+    ("phoenix", "Phoenix"),
+    ("primalbeast", "Primal Beast"),
+    ("puck", "Puck"),
+    ("pudge", "Pudge"),
+    ("pugna", "Pugna"),
+    ("queenofpain", "Queen of Pain"),
+    ("rattletrap", "Clockwerk"),
+    ("razor", "Razor"),
+    ("riki", "Riki"),
+    ("ringmaster", "Ringmaster"),
+    ("rubick", "Rubick"),
+    ("sandking", "Sand King"),
+    ("shadowdemon", "Shadow Demon"),
+    ("shadowshaman", "Shadow Shaman"),
+    ("shredder", "Timbersaw"),
+    ("silencer", "Silencer"),
+    ("skeletonking", "Wraith King"),
+    ("skywrathmage", "Skywrath Mage"),
+    ("slardar", "Slardar"),
+    ("slark", "Slark"),
+    ("snapfire", "Snapfire"),
+    ("sniper", "Sniper"),
+    ("spectre", "Spectre"),
+    ("spiritbreaker", "Spirit Breaker"),
+    ("stormspirit", "Storm Spirit"),
+    ("sven", "Sven"),
+    ("techies", "Techies"),
+    ("templarassassin", "Templar Assassin"),
+    ("terrorblade", "Terrorblade"),
+    ("tidehunter", "Tidehunter"),
+    ("tinker", "Tinker"),
+    ("tiny", "Tiny"),
+    ("treant", "Treant Protector"),
+    ("trollwarlord", "Troll Warlord"),
+    ("tusk", "Tusk"),
+    ("undying", "Undying"),
+    ("ursa", "Ursa"),
+    ("vengefulspirit", "Vengeful Spirit"),
+    ("venomancer", "Venomancer"),
+    ("viper", "Viper"),
+    ("visage", "Visage"),
+    ("voidspirit", "Void Spirit"),
+    ("warlock", "Warlock"),
+    ("weaver", "Weaver"),
+    ("windrunner", "Windranger"),
+    ("winterwyvern", "Winter Wyvern"),
+    ("wisp", "Io"),
+    ("witchdoctor", "Witch Doctor"),
+    ("zuus", "Zeus"),
+];
+
+/// In-game name of a hero given its entity class or unit name, e.g.
+/// `CDOTA_Unit_Hero_Nevermore` or `npc_dota_hero_nevermore` -> `Shadow Fiend`.
+/// `None` for names that aren't heroes or heroes missing from the table.
+pub fn hero_display_name(name: &str) -> Option<&'static str> {
+    let key = name
+        .trim_start_matches("CDOTA_Unit_Hero_")
+        .trim_start_matches("npc_dota_hero_")
+        .chars()
+        .filter(|&c| c != '_')
+        .collect::<String>()
+        .to_ascii_lowercase();
+    HEROES
+        .iter()
+        .find(|(hero, _)| *hero == key)
+        .map(|(_, display_name)| *display_name)
+}
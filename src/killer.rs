@@ -0,0 +1,169 @@
+use d2_stampede::prelude::*;
+use d2_stampede_observers::players::*;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::rc::Rc;
+
+use crate::heroes::hero_display_name;
+
+/// Unit that killed a ward.
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Killer {
+    /// One of `hero`, `creep`, `tower`, `summon`, `illusion`, `courier` or
+    /// `neutral`.
+    pub kind: String,
+    pub name: String,
+    /// Combat log name of the unit, e.g. `npc_dota_lycan_wolf1`.
+    pub unit: String,
+    /// Combat log name of the damage source, which is the owning hero for
+    /// summons.
+    pub source: String,
+    pub team: Option<i32>,
+    pub player_slot: Option<usize>,
+    pub steam_id: Option<u64>,
+}
+
+impl Killer {
+    /// Builds killer from a ward death combat log entry. Heroes, summons and
+    /// illusions are credited to the player controlling the damage source,
+    /// which combat log reports as the owning hero for summons.
+    pub fn from_combat_log(cle: &CombatLogEntry, players: &Players) -> anyhow::Result<Self> {
+        let source = cle.damage_source_name()?;
+        let unit = cle.attacker_name().unwrap_or(source);
+        let kind = killer_kind(
+            unit,
+            cle.is_attacker_illusion().unwrap_or_default(),
+            cle.is_attacker_hero().unwrap_or_default(),
+        );
+        let player = players
+            .hero_to_player
+            .get(source)
+            .or_else(|| players.hero_to_player.get(unit));
+
+        Ok(Killer {
+            kind: kind.to_string(),
+            name: display_name(unit),
+            unit: unit.to_string(),
+            source: source.to_string(),
+            team: cle
+                .attacker_team()
+                .ok()
+                .map(|team| team as i32)
+                .or(player.map(|p| p.team)),
+            player_slot: player.and_then(|p| player_slot(players, p)),
+            steam_id: player.map(|p| p.id),
+        })
+    }
+
+    /// Builds killer from the combat log name only.
+    pub fn from_name(unit: &str, players: &Players) -> Self {
+        let player = players.hero_to_player.get(unit);
+        Killer {
+            kind: unit_kind(unit).to_string(),
+            name: display_name(unit),
+            unit: unit.to_string(),
+            source: unit.to_string(),
+            team: player.map(|p| p.team),
+            player_slot: player.and_then(|p| player_slot(players, p)),
+            steam_id: player.map(|p| p.id),
+        }
+    }
+}
+
+fn player_slot(players: &Players, player: &Rc<Player>) -> Option<usize> {
+    players.players.iter().position(|p| Rc::ptr_eq(p, player))
+}
+
+/// Illusions carry the name of the hero they copy, so only the combat log
+/// flags tell them apart.
+fn killer_kind(unit: &str, is_illusion: bool, is_hero: bool) -> &'static str {
+    if is_illusion {
+        "illusion"
+    } else if is_hero {
+        "hero"
+    } else {
+        unit_kind(unit)
+    }
+}
+
+fn unit_kind(unit: &str) -> &'static str {
+    if unit.starts_with("npc_dota_hero_") {
+        "hero"
+    } else if unit.contains("courier") {
+        "courier"
+    } else if unit.contains("tower") {
+        "tower"
+    } else if unit.contains("neutral") {
+        "neutral"
+    } else if unit.contains("creep") || unit.contains("goodguys") || unit.contains("badguys") {
+        "creep"
+    } else {
+        "summon"
+    }
+}
+
+/// `npc_dota_hero_nevermore` -> `Shadow Fiend`,
+/// `npc_dota_lycan_wolf1` -> `Lycan Wolf`.
+fn display_name(unit: &str) -> String {
+    if let Some(hero) = hero_display_name(unit) {
+        return hero.to_string();
+    }
+    unit.trim_start_matches("npc_dota_hero_")
+        .trim_start_matches("npc_dota_")
+        .split('_')
+        .map(|word| word.trim_end_matches(|c: char| c.is_ascii_digit()))
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summons_are_named_after_their_unit() {
+        assert_eq!(unit_kind("npc_dota_lycan_wolf1"), "summon");
+        assert_eq!(display_name("npc_dota_lycan_wolf1"), "Lycan Wolf");
+        assert_eq!(display_name("npc_dota_beastmaster_boar"), "Beastmaster Boar");
+    }
+
+    #[test]
+    fn illusions_are_told_apart_by_combat_log_flags() {
+        assert_eq!(killer_kind("npc_dota_hero_phantom_lancer", true, true), "illusion");
+        assert_eq!(killer_kind("npc_dota_hero_phantom_lancer", false, true), "hero");
+        assert_eq!(display_name("npc_dota_hero_phantom_lancer"), "Phantom Lancer");
+        assert_eq!(killer_kind("npc_dota_lycan_wolf1", false, false), "summon");
+    }
+
+    #[test]
+    fn creeps_and_towers() {
+        assert_eq!(unit_kind("npc_dota_creep_goodguys_melee"), "creep");
+        assert_eq!(unit_kind("npc_dota_badguys_siege"), "creep");
+        assert_eq!(unit_kind("npc_dota_goodguys_tower1_mid"), "tower");
+        assert_eq!(unit_kind("npc_dota_badguys_tower4"), "tower");
+        assert_eq!(unit_kind("npc_dota_neutral_dark_troll"), "neutral");
+        assert_eq!(unit_kind("npc_dota_courier"), "courier");
+        assert_eq!(display_name("npc_dota_creep_goodguys_melee"), "Creep Goodguys Melee");
+        assert_eq!(display_name("npc_dota_goodguys_tower1_mid"), "Goodguys Tower Mid");
+    }
+
+    #[test]
+    fn heroes_use_in_game_names() {
+        assert_eq!(unit_kind("npc_dota_hero_nevermore"), "hero");
+        assert_eq!(display_name("npc_dota_hero_nevermore"), "Shadow Fiend");
+        assert_eq!(display_name("npc_dota_hero_skeleton_king"), "Wraith King");
+        assert_eq!(hero_display_name("CDOTA_Unit_Hero_Rattletrap"), Some("Clockwerk"));
+        assert_eq!(hero_display_name("CDOTA_Unit_Hero_DoomBringer"), Some("Doom"));
+        assert_eq!(hero_display_name("npc_dota_lycan_wolf1"), None);
+    }
+}
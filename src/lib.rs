@@ -1,43 +1,196 @@
-use anyhow::bail;
+//! Ward events from Dota 2 replays.
+//!
+//! [`parse`] returns every ward placed during the game, [`parse_with`] adds
+//! partial results, progress reporting, timeouts and OpenDota ward logs.
+//! Python bindings are built with the `python` feature.
+
+// pyo3 0.22 expands `PyResult` returns into a `PyErr -> PyErr` conversion.
+#![cfg_attr(feature = "python", allow(clippy::useless_conversion))]
+
 use hashbrown::HashMap;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
 use std::collections::VecDeque;
+use std::panic::AssertUnwindSafe;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 
+use d2_stampede::error::ParserError;
 use d2_stampede::prelude::*;
+use d2_stampede::proto::{CSvcMsgServerInfo, DotaCombatlogTypes, DotaGameState};
 use d2_stampede_observers::game_time::*;
 use d2_stampede_observers::players::*;
 use d2_stampede_observers::wards::*;
 
+use crate::coords::*;
+use crate::economy::*;
+use crate::error::*;
+use crate::heroes::*;
+use crate::match_info::player_name;
+use crate::pauses::*;
+use crate::progress::*;
+
+#[cfg(feature = "export")]
+pub use crate::columnar::{advantage_batch, record_batch};
+pub use crate::economy::{AdvantageSample, Economy};
+pub use crate::error::{ErrorKind, ParseError};
+#[cfg(feature = "export")]
+pub use crate::export::{write_records, ExportFormat, RecordsWriter};
+pub use crate::killer::Killer;
+pub use crate::match_info::{MatchInfo, PlayerInfo};
+pub use crate::opendota::{OpenDotaLogs, OpenDotaWard};
+pub use crate::progress::{Progress, ProgressCallback};
+pub use crate::source::ReplayData;
+
+#[cfg(feature = "export")]
+mod columnar;
+pub mod coords;
+mod economy;
+mod error;
+#[cfg(feature = "export")]
+mod export;
+mod heroes;
+mod killer;
+mod match_info;
+mod opendota;
+mod pauses;
+mod progress;
+#[cfg(feature = "python")]
+mod python;
+mod source;
+#[cfg(feature = "python")]
+mod stream;
+
 #[derive(Debug, Copy, Clone)]
 pub struct WardEntry {
+    pub ward_id: u32,
     pub hero_handle: usize,
-    pub placed_tick: i32,
+    pub player_slot: usize,
+    pub placed_tick: u32,
     pub is_radiant: bool,
     pub is_observer: bool,
 }
 
-#[pyclass(get_all, set_all)]
-#[derive(Clone)]
-pub struct Output {
+/// Single ward event. Exposed to Python as `Output`.
+#[cfg_attr(feature = "python", pyclass(name = "Output", get_all, set_all))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WardRecord {
+    pub ward_id: u32,
     pub time_placed: i32,
     pub duration: i32,
+    pub paused_duration: i32,
+    pub raw_tick_placed: u32,
+    pub raw_tick: u32,
     pub is_obs: bool,
     pub is_radiant: bool,
     pub event: String,
+    pub is_deny: bool,
     pub post_game: bool,
+    /// Game state the event happened in: `strategy` (hero selection and
+    /// strategy time), `pre_game` (horn countdown), `in_game` or `post_game`.
+    pub game_phase: String,
+    /// `0` for bots, or if the placing player couldn't be resolved.
     pub player_placed_steam_id: u64,
+    /// Player id of the placer, `0..5` for Radiant and `5..10` for Dire.
+    pub player_placed_slot: usize,
+    pub player_placed_team: i32,
+    /// Hero entity class, e.g. `CDOTA_Unit_Hero_ShadowShaman`.
+    pub player_placed_hero: Option<String>,
+    /// In-game hero name, e.g. `Shadow Fiend` for `CDOTA_Unit_Hero_Nevermore`.
+    /// `None` for heroes this version doesn't know.
+    pub player_placed_hero_name: Option<String>,
+    /// Name shown in game, e.g. to tell bots and anonymous players apart.
+    pub player_placed_name: Option<String>,
+    /// Placer's economy at this event, i.e. at placement or destruction.
+    pub player_placed_economy: Option<Economy>,
     pub player_destroyed_steam_id: Option<u64>,
+    /// Player controlling the unit that killed the ward, if any.
+    pub player_destroyed_slot: Option<usize>,
+    pub player_destroyed_team: Option<i32>,
+    pub player_destroyed_hero: Option<String>,
+    pub player_destroyed_hero_name: Option<String>,
+    pub player_destroyed_name: Option<String>,
+    pub player_destroyed_economy: Option<Economy>,
     pub npc_killed: Option<String>,
+    pub killer: Option<Killer>,
     pub x: u16,
     pub y: u16,
     pub z: u16,
     pub vec_x: f32,
     pub vec_y: f32,
     pub vec_z: f32,
+    pub world_x: f32,
+    pub world_y: f32,
+    pub world_z: f32,
+    pub minimap_x: f32,
+    pub minimap_y: f32,
+    /// Team totals at this event, summed over all five players. Earlier
+    /// versions reported the net worth of a single player here (team slot 2
+    /// for Radiant, 3 for Dire), so older values aren't comparable.
     pub radiant_networth: i32,
     pub dire_networth: i32,
+    pub radiant_xp: i32,
+    pub dire_xp: i32,
+}
+
+#[cfg_attr(feature = "python", pyclass(get_all))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParseResult {
+    pub match_info: MatchInfo,
+    pub wards: Vec<WardRecord>,
+    /// Team net worth and experience sampled every
+    /// [`ParseOptions::advantage_interval`] ticks from the horn.
+    pub advantage: Vec<AdvantageSample>,
+    /// Ward logs in OpenDota's parsed match format, set if
+    /// [`ParseOptions::opendota`] is.
+    pub opendota: Option<OpenDotaLogs>,
+    pub ticks_per_second: f32,
+    pub warnings: Vec<String>,
+    /// Set if parsing stopped early, `wards` then hold everything resolved up
+    /// to that point.
+    pub error: Option<ParseError>,
+}
+
+/// Ward event waiting for the game start time. Everything that changes over
+/// the game is read when the event happens, see [`App::queue`].
+struct PendingEntry {
+    handle: u32,
+    tick: u32,
+    event: WardEvent,
+    killer: Option<Killer>,
+    game_phase: &'static str,
+    /// Cell and offset of the ward position along X, Y and Z.
+    position: [(u16, f32); 3],
+    /// Radiant and Dire net worth and experience.
+    team_totals: [(i32, i32); 2],
+    placer_economy: Option<Economy>,
+    destroyer_economy: Option<Economy>,
+}
+
+/// Options for [`parse_with`].
+#[derive(Default)]
+pub struct ParseOptions {
+    /// Return everything parsed before a replay failed midway, with
+    /// [`ParseResult::error`] describing where it stopped, instead of the
+    /// error.
+    pub allow_partial: bool,
+    /// Receives ward events as soon as they are resolved instead of
+    /// collecting them into [`ParseResult::wards`].
+    pub on_event: Option<Box<dyn FnMut(WardRecord) -> ObserverResult>>,
+    /// Called every `progress_interval` ticks, parsing is cancelled when it
+    /// returns `false` or fails.
+    pub on_progress: Option<ProgressCallback>,
+    pub progress_interval: u32,
+    pub timeout: Option<Duration>,
+    /// Game ticks between [`ParseResult::advantage`] samples, one game minute
+    /// by default.
+    pub advantage_interval: Option<u32>,
+    /// Also builds [`ParseResult::opendota`].
+    pub opendota: bool,
 }
 
 #[derive(Default)]
@@ -45,112 +198,484 @@ struct App {
     game_time: Rc<RefCell<GameTime>>,
     players: Rc<RefCell<Players>>,
 
+    match_info: MatchInfo,
+    ticks_per_second: f32,
+    next_ward_id: u32,
     handle_to_entry: HashMap<u32, WardEntry>,
-    pending_entries: VecDeque<(Entity, i32, WardEvent)>,
-    result: Vec<Output>,
+    pending_entries: VecDeque<PendingEntry>,
+    killers: HashMap<WardClass, VecDeque<Killer>>,
+    pauses: Pauses,
+    result: Vec<WardRecord>,
+    advantage: Vec<AdvantageSample>,
+    advantage_interval: u32,
+    next_advantage_tick: u32,
+    opendota: Option<OpenDotaLogs>,
+    warnings: Vec<String>,
+    error: Option<ParseError>,
+    on_event: Option<Box<dyn FnMut(WardRecord) -> ObserverResult>>,
+    watchdog: Watchdog,
 }
 
-#[observer]
 impl App {
-    #[on_tick_end]
-    fn tick_end(&mut self, ctx: &Context) -> ObserverResult {
-        if let Ok(start_time) = self.game_time.borrow().start_time() {
-            while let Some((ward, tick, event)) = self.pending_entries.pop_front() {
-                let handle = ward.handle();
-                let output = Output {
-                    time_placed: (self.handle_to_entry[&handle].placed_tick as f32 / 30.0 - start_time) as i32,
-                    duration: (((tick - self.handle_to_entry[&handle].placed_tick) as f32) / 30.0) as i32,
-                    is_obs: self.handle_to_entry[&handle].is_observer,
-                    is_radiant: self.handle_to_entry[&handle].is_radiant,
-                    event: match event {
-                        WardEvent::Killed(_) => "killed".to_string(),
-                        WardEvent::Expired => "expired".to_string(),
-                        _ => unreachable!(),
-                    },
-                    post_game: false,
-                    player_placed_steam_id: self.players.borrow().handle_to_player
-                        [&self.handle_to_entry[&handle].hero_handle]
-                        .id,
-                    player_destroyed_steam_id: if let WardEvent::Killed(killer) = &event {
-                        self.players.borrow().hero_to_player.get(killer).map(|x| x.id)
-                    } else {
-                        None
-                    },
-                    npc_killed: if let WardEvent::Killed(killer) = &event {
-                        Some(killer.to_string())
-                    } else {
-                        None
-                    },
-                    x: property!(ward, "CBodyComponent.m_cellX"),
-                    y: property!(ward, "CBodyComponent.m_cellY"),
-                    z: property!(ward, "CBodyComponent.m_cellZ"),
-                    vec_x: property!(ward, "CBodyComponent.m_vecX"),
-                    vec_y: property!(ward, "CBodyComponent.m_vecY"),
-                    vec_z: property!(ward, "CBodyComponent.m_vecZ"),
-                    radiant_networth: property!(
-                        ctx.entities().get_by_class_name("CDOTA_DataRadiant")?,
-                        "m_vecDataTeam.0002.m_iNetWorth"
-                    ),
-                    dire_networth: property!(
-                        ctx.entities().get_by_class_name("CDOTA_DataDire")?,
-                        "m_vecDataTeam.0003.m_iNetWorth"
-                    ),
-                };
-                self.result.push(output);
+    fn warn(&mut self, ctx: &Context, message: String) {
+        self.warnings.push(format!("tick {}: {message}", ctx.net_tick()));
+    }
+
+    /// Samples team totals once `advantage_interval` game ticks have passed
+    /// since the previous sample, from the horn until the game ends.
+    fn sample_advantage(&mut self, ctx: &Context) {
+        let Ok(start_time) = self.game_time.borrow().start_time() else {
+            return;
+        };
+        let since_horn = self.pauses.game_tick(ctx.net_tick()) as f32 - start_time * self.ticks_per_second;
+        if since_horn < self.next_advantage_tick as f32 || game_phase(ctx) == "post_game" {
+            return;
+        }
+        let interval = self.advantage_interval.max(1);
+        let time = (since_horn / self.ticks_per_second) as i32;
+        if let Ok(sample) = AdvantageSample::new(ctx, time) {
+            self.advantage.push(sample);
+        }
+        self.next_advantage_tick = (since_horn as u32 / interval + 1) * interval;
+    }
+
+    /// Queues `event` of `ward` until it can be resolved, reading the game
+    /// phase, ward position, team totals and economy of the placer and
+    /// destroyer as they are at the current tick.
+    fn queue(&mut self, ctx: &Context, ward: &Entity, event: WardEvent, killer: Option<Killer>) {
+        let handle = ward.handle();
+        let position = ["X", "Y", "Z"].map(|axis| {
+            try_property!(ward, u16, "CBodyComponent.m_cell{axis}").zip(try_property!(
+                ward,
+                f32,
+                "CBodyComponent.m_vec{axis}"
+            ))
+        });
+        if position.contains(&None) {
+            self.warn(ctx, format!("Couldn't read position of ward {handle}, using 0"));
+        }
+        let team_totals = [2, 3].map(|team| team_totals(ctx, team));
+        for e in team_totals.iter().filter_map(|totals| totals.as_ref().err()) {
+            self.warn(
+                ctx,
+                format!("Couldn't read team totals for ward {handle}, using 0: {e}"),
+            );
+        }
+        let (placer_economy, destroyer_economy) = {
+            let players = self.players.borrow();
+            let placer = self
+                .handle_to_entry
+                .get(&handle)
+                .and_then(|entry| players.handle_to_player.get(&entry.hero_handle));
+            let destroyer = killer
+                .as_ref()
+                .and_then(|x| x.player_slot)
+                .and_then(|slot| players.players.get(slot));
+            (
+                placer.and_then(|x| Economy::new(ctx, x)),
+                destroyer.and_then(|x| Economy::new(ctx, x)),
+            )
+        };
+        self.pending_entries.push_back(PendingEntry {
+            handle,
+            tick: ctx.net_tick(),
+            event,
+            killer,
+            game_phase: game_phase(ctx),
+            position: position.map(Option::unwrap_or_default),
+            team_totals: team_totals.map(Result::unwrap_or_default),
+            placer_economy,
+            destroyer_economy,
+        });
+    }
+
+    /// Resolves queued ward events. Events stay queued until the game start
+    /// time is known, see [`App::flush_remaining`] for the last flush.
+    fn flush(&mut self, ctx: &Context, game_ended: bool) -> ObserverResult {
+        let Ok(start_time) = self.game_time.borrow().start_time() else {
+            return Ok(());
+        };
+        while let Some(PendingEntry {
+            handle,
+            tick,
+            event,
+            killer,
+            game_phase,
+            position,
+            team_totals,
+            placer_economy,
+            destroyer_economy,
+        }) = self.pending_entries.pop_front()
+        {
+            let killer = killer.filter(|_| !game_ended);
+            let Some(entry) = self.handle_to_entry.get(&handle).copied() else {
+                self.warn(ctx, format!("{event:?} ward {handle} was never seen placed, skipping"));
+                continue;
+            };
+            let placer = self.players.borrow().handle_to_player.get(&entry.hero_handle).cloned();
+            if placer.is_none() {
+                self.warn(
+                    ctx,
+                    format!("No player for hero {} placing ward {handle}", entry.hero_handle),
+                );
+            }
+            let destroyer = killer
+                .as_ref()
+                .and_then(|x| x.player_slot)
+                .and_then(|slot| self.players.borrow().players.get(slot).cloned());
+            let [(cell_x, vec_x), (cell_y, vec_y), (cell_z, vec_z)] = position;
+            let (world_x, world_y, world_z) = (
+                cell_to_world(cell_x, vec_x),
+                cell_to_world(cell_y, vec_y),
+                cell_to_world(cell_z, vec_z),
+            );
+            let (minimap_x, minimap_y) = world_to_minimap(world_x, world_y);
+            let [(radiant_networth, radiant_xp), (dire_networth, dire_xp)] = team_totals;
+            let phase = if game_ended && event != WardEvent::Placed {
+                "post_game"
+            } else {
+                game_phase
+            };
+            let game_tick_placed = self.pauses.game_tick(entry.placed_tick);
+            let game_tick = self.pauses.game_tick(tick);
+            let tps = self.ticks_per_second;
+            let output = WardRecord {
+                ward_id: entry.ward_id,
+                time_placed: (game_tick_placed as f32 / tps - start_time) as i32,
+                duration: ((game_tick - game_tick_placed) as f32 / tps) as i32,
+                paused_duration: (self.pauses.paused_ticks(entry.placed_tick, tick) as f32 / tps) as i32,
+                raw_tick_placed: entry.placed_tick,
+                raw_tick: tick,
+                is_obs: entry.is_observer,
+                is_radiant: entry.is_radiant,
+                event: match event {
+                    WardEvent::Placed => "placed".to_string(),
+                    _ if game_ended => "game_end".to_string(),
+                    WardEvent::Killed(_) => "killed".to_string(),
+                    WardEvent::Expired => "expired".to_string(),
+                },
+                is_deny: killer
+                    .as_ref()
+                    .and_then(|x| x.team)
+                    .is_some_and(|team| team == if entry.is_radiant { 2 } else { 3 }),
+                post_game: phase == "post_game",
+                game_phase: phase.to_string(),
+                player_placed_steam_id: placer.as_ref().map_or(0, |x| x.id),
+                player_placed_slot: entry.player_slot,
+                player_placed_team: if entry.is_radiant { 2 } else { 3 },
+                player_placed_hero: placer.as_ref().map(|x| x.hero.to_string()),
+                player_placed_hero_name: placer
+                    .as_ref()
+                    .and_then(|x| hero_display_name(&x.hero))
+                    .map(str::to_string),
+                player_placed_name: player_name(ctx, entry.player_slot),
+                player_placed_economy: placer_economy,
+                player_destroyed_steam_id: killer.as_ref().and_then(|x| x.steam_id),
+                player_destroyed_slot: killer.as_ref().and_then(|x| x.player_slot),
+                player_destroyed_team: destroyer.as_ref().map(|x| x.team),
+                player_destroyed_hero: destroyer.as_ref().map(|x| x.hero.to_string()),
+                player_destroyed_hero_name: destroyer
+                    .as_ref()
+                    .and_then(|x| hero_display_name(&x.hero))
+                    .map(str::to_string),
+                player_destroyed_name: killer
+                    .as_ref()
+                    .and_then(|x| x.player_slot)
+                    .and_then(|slot| player_name(ctx, slot)),
+                player_destroyed_economy: destroyer_economy.filter(|_| !game_ended),
+                npc_killed: match &event {
+                    WardEvent::Killed(killer) if !game_ended => Some(killer.to_string()),
+                    _ => None,
+                },
+                killer,
+                x: cell_x,
+                y: cell_y,
+                z: cell_z,
+                vec_x,
+                vec_y,
+                vec_z,
+                world_x,
+                world_y,
+                world_z,
+                minimap_x,
+                minimap_y,
+                radiant_networth,
+                dire_networth,
+                radiant_xp,
+                dire_xp,
+            };
+            if event != WardEvent::Placed {
+                self.handle_to_entry.remove(&handle);
+            }
+            if let Some(opendota) = self
+                .opendota
+                .as_mut()
+                .filter(|_| event == WardEvent::Placed || !game_ended)
+            {
+                opendota.push(
+                    (game_tick as f32 / tps - start_time) as i32,
+                    entry.is_observer,
+                    event != WardEvent::Placed,
+                    entry.player_slot as i32,
+                    handle,
+                    output.npc_killed.clone(),
+                    (output.x, output.y, output.z),
+                    (output.vec_x, output.vec_y),
+                );
+            }
+            match &mut self.on_event {
+                Some(on_event) => on_event(output)?,
+                None => self.result.push(output),
             }
         }
         Ok(())
     }
+
+    /// Emits every ward that is still standing when the replay ends. Must be
+    /// called once after [`Parser::run_to_end`], which makes [`Wards`] report
+    /// the remaining wards from its epilogue.
+    fn game_end(&mut self, ctx: &Context) -> ObserverResult {
+        for handle in self.standing_wards() {
+            match ctx.entities().get_by_handle(handle as usize) {
+                Ok(ward) => self.queue(ctx, ward, WardEvent::Expired, None),
+                Err(e) => self.warn(
+                    ctx,
+                    format!("Ward {handle} standing at game end is gone, skipping: {e}"),
+                ),
+            }
+        }
+        self.flush_remaining(ctx, true)
+    }
+
+    /// Last flush once parsing has ended, warning about events that can't be
+    /// resolved as the game never started.
+    fn flush_remaining(&mut self, ctx: &Context, game_ended: bool) -> ObserverResult {
+        if self.game_time.borrow().start_time().is_err() && !self.pending_entries.is_empty() {
+            let pending = self.pending_entries.len();
+            self.warn(
+                ctx,
+                format!("Game start time unknown, dropping {pending} unresolved ward events"),
+            );
+            self.pending_entries.clear();
+        }
+        self.flush(ctx, game_ended)
+    }
+
+    /// Handles of placed wards without a pending kill or expiry.
+    fn standing_wards(&self) -> Vec<u32> {
+        let pending = self
+            .pending_entries
+            .iter()
+            .filter(|pending| pending.event != WardEvent::Placed)
+            .map(|pending| pending.handle)
+            .collect::<Vec<_>>();
+        self.handle_to_entry
+            .keys()
+            .copied()
+            .filter(|handle| !pending.contains(handle))
+            .collect()
+    }
+
+    /// Called instead of [`App::game_end`] when replay data ends before the
+    /// game did. Wards still standing have no known fate, so they are left
+    /// out. That includes the kills and expiries [`Wards`] reports for them
+    /// from its epilogue, which are the only ones still queued once
+    /// [`Parser::run_to_end`] returns.
+    fn data_end(&mut self, ctx: &Context) {
+        self.pending_entries
+            .retain(|pending| pending.event == WardEvent::Placed);
+        let standing = self.standing_wards().len();
+        if standing > 0 {
+            self.warn(
+                ctx,
+                format!("{standing} wards still standing where replay data ends, leaving them unresolved"),
+            );
+        }
+    }
+
+    /// Records that parsing stopped at the current tick, keeping everything
+    /// resolved so far.
+    fn stop(&mut self, ctx: &Context, mut error: ParseError) {
+        if let Err(e) = self.flush_remaining(ctx, false) {
+            self.warn(ctx, format!("Couldn't resolve pending wards: {e}"));
+        }
+        let game_time = game_time_at(
+            &self.game_time.borrow(),
+            &self.pauses,
+            self.ticks_per_second,
+            ctx.net_tick(),
+        );
+        error.tick = Some(ctx.net_tick()).filter(|&tick| tick != u32::MAX);
+        error.game_time = game_time;
+        self.error = Some(error);
+    }
+}
+
+#[observer]
+impl App {
+    #[on_message]
+    fn server_info(&mut self, _ctx: &Context, info: CSvcMsgServerInfo) -> ObserverResult {
+        if info.tick_interval() > 0.0 {
+            self.ticks_per_second = 1.0 / info.tick_interval();
+        }
+        Ok(())
+    }
+
+    /// Mirrors the killer queue of [`Wards`], keeping the whole combat log
+    /// entry instead of the damage source name only.
+    #[on_combat_log]
+    fn combat_log(&mut self, _ctx: &Context, cle: &CombatLogEntry) -> ObserverResult {
+        if cle.r#type() != DotaCombatlogTypes::DotaCombatlogDeath {
+            return Ok(());
+        }
+        let Some(ward_class) = cle.target_name().ok().and_then(ward_class_from_target_name) else {
+            return Ok(());
+        };
+        let is_ward_attacker = cle.attacker_name().ok().and_then(ward_class_from_target_name).is_some();
+        if cle.damage_source_name().is_err() || is_ward_attacker {
+            return Ok(());
+        }
+        let killer = Killer::from_combat_log(cle, &self.players.borrow())?;
+        self.killers.entry(ward_class).or_default().push_back(killer);
+        Ok(())
+    }
+
+    #[on_tick_end]
+    fn tick_end(&mut self, ctx: &Context) -> ObserverResult {
+        self.pauses.update(ctx)?;
+        self.flush(ctx, false)?;
+        self.sample_advantage(ctx);
+        let tick = ctx.net_tick();
+        self.watchdog.check(tick, || {
+            game_time_at(&self.game_time.borrow(), &self.pauses, self.ticks_per_second, tick)
+        })
+    }
 }
 
 impl WardsObserver for App {
     fn on_ward(&mut self, ctx: &Context, ward_class: WardClass, event: WardEvent, ward: &Entity) -> ObserverResult {
         match event {
             WardEvent::Placed => {
-                let owner_handle: usize = property!(ward, "m_hOwnerEntity");
-                let owner = ctx.entities().get_by_handle(owner_handle)?;
-                let mut player_slot: usize = if let Some(x) = try_property!(owner, "m_nPlayerID") {
-                    x
-                } else if let Some(x) = try_property!(owner, "m_iPlayerID") {
-                    x
-                } else {
-                    bail!("Couldn't get player slot from ward entity")
+                let Some(owner) = try_property!(ward, usize, "m_hOwnerEntity")
+                    .and_then(|owner_handle| ctx.entities().get_by_handle(owner_handle).ok())
+                else {
+                    self.warn(ctx, format!("Couldn't get owner of ward {}, skipping", ward.handle()));
+                    return Ok(());
+                };
+                let Some(player_slot) = try_property!(owner, usize, "m_nPlayerID")
+                    .or_else(|| try_property!(owner, usize, "m_iPlayerID"))
+                    .map(|x| x >> 1)
+                else {
+                    self.warn(
+                        ctx,
+                        format!("Couldn't get player slot of ward {}, skipping", ward.handle()),
+                    );
+                    return Ok(());
+                };
+                let Some(player) = self.players.borrow().players.get(player_slot).cloned() else {
+                    self.warn(
+                        ctx,
+                        format!("No player {player_slot} for ward {}, skipping", ward.handle()),
+                    );
+                    return Ok(());
                 };
-                player_slot >>= 1;
-
-                let player = &self.players.borrow().players[player_slot];
                 let hero_handle = player.hero_handle;
+                let tick = ctx.net_tick();
 
+                self.next_ward_id += 1;
                 self.handle_to_entry.insert(
                     ward.handle(),
                     WardEntry {
+                        ward_id: self.next_ward_id,
                         hero_handle,
-                        placed_tick: self.game_time.borrow().tick(ctx)?,
+                        player_slot,
+                        placed_tick: tick,
                         is_radiant: player.team == 2,
                         is_observer: ward_class == WardClass::Observer,
                     },
                 );
+                self.queue(ctx, ward, WardEvent::Placed, None);
             }
-            WardEvent::Killed(killer) => {
-                self.pending_entries.push_back((
-                    ward.clone(),
-                    self.game_time.borrow().tick(ctx)?,
-                    WardEvent::Killed(killer),
-                ));
+            WardEvent::Killed(name) => {
+                let killer = self
+                    .killers
+                    .get_mut(&ward_class)
+                    .and_then(|killers| killers.pop_front())
+                    .filter(|killer| name.as_ref() == killer.source)
+                    .unwrap_or_else(|| Killer::from_name(&name, &self.players.borrow()));
+                self.queue(ctx, ward, WardEvent::Killed(name), Some(killer));
             }
             WardEvent::Expired => {
-                self.pending_entries
-                    .push_back((ward.clone(), self.game_time.borrow().tick(ctx)?, WardEvent::Expired));
+                self.queue(ctx, ward, WardEvent::Expired, None);
             }
         }
         Ok(())
     }
 }
 
-#[pyfunction]
-pub fn parse_replay(data: &[u8]) -> PyResult<Vec<Output>> {
-    std::panic::catch_unwind(|| {
-        let mut parser = Parser::new(data)?;
+fn ward_class_from_target_name(name: &str) -> Option<WardClass> {
+    match name {
+        "npc_dota_observer_wards" => Some(WardClass::Observer),
+        "npc_dota_sentry_wards" => Some(WardClass::Sentry),
+        _ => None,
+    }
+}
+
+/// Phase of the game from game rules state, see [`WardRecord::game_phase`].
+/// States before the horn countdown, including the time before game rules
+/// exist, are `strategy`.
+fn game_phase(ctx: &Context) -> &'static str {
+    let state = ctx
+        .entities()
+        .get_by_class_name("CDOTAGamerulesProxy")
+        .ok()
+        .and_then(|game_rules| try_property!(game_rules, i32, "m_pGameRules.m_nGameState"))
+        .and_then(|state| DotaGameState::try_from(state).ok());
+    match state {
+        Some(DotaGameState::DotaGamerulesStatePreGame) => "pre_game",
+        Some(DotaGameState::DotaGamerulesStateGameInProgress) => "in_game",
+        Some(DotaGameState::DotaGamerulesStatePostGame | DotaGameState::DotaGamerulesStateDisconnect) => "post_game",
+        _ => "strategy",
+    }
+}
+
+/// Game clock time in seconds at raw `tick`, unknown before the game has
+/// started.
+fn game_time_at(game_time: &GameTime, pauses: &Pauses, ticks_per_second: f32, tick: u32) -> Option<f32> {
+    let start_time = game_time.start_time().ok()?;
+    Some(pauses.game_tick(tick) as f32 / ticks_per_second - start_time)
+}
+
+/// Replays cut short (interrupted downloads, crashed servers) lack the
+/// trailing `CDemoFileInfo` the header points to. Appends an empty one so the
+/// parser accepts the replay and runs up to the point where data ends.
+fn patch_truncated(data: &[u8]) -> Vec<u8> {
+    let mut patched = data.to_vec();
+    let offset = patched.len() as u32;
+    patched[8..12].copy_from_slice(&offset.to_le_bytes());
+    patched.extend_from_slice(&[EDemoCommands::DemFileInfo as u8, 0, 0]);
+    patched
+}
+
+fn run(data: &[u8], options: ParseOptions) -> Result<App, ParseError> {
+    let started = Instant::now();
+    std::panic::catch_unwind(AssertUnwindSafe(|| {
+        let patched;
+        let (mut parser, truncated) = match Parser::new(data) {
+            Err(ParserError::WrongMagic) => return Err(WrongFormat::of(data).into()),
+            Err(ParserError::ReplayEncodingError) => {
+                patched = patch_truncated(data);
+                (Parser::new(&patched)?, true)
+            }
+            parser => (parser?, false),
+        };
+        let replay_info = parser.replay_info().clone();
+        let ticks_per_second = if replay_info.playback_time() > 0.0 {
+            replay_info.playback_ticks() as f32 / replay_info.playback_time()
+        } else {
+            30.0
+        };
+        let total_ticks = replay_info.playback_ticks().max(0) as u32;
 
         let game_time = parser.register_observer::<GameTime>();
         let players = parser.register_observer::<Players>();
@@ -161,25 +686,182 @@ pub fn parse_replay(data: &[u8]) -> PyResult<Vec<Output>> {
 
         app.borrow_mut().game_time = game_time;
         app.borrow_mut().players = players;
+        app.borrow_mut().ticks_per_second = ticks_per_second;
+        app.borrow_mut().on_event = options.on_event;
+        app.borrow_mut().opendota = options.opendota.then(OpenDotaLogs::default);
+        app.borrow_mut().advantage_interval = options
+            .advantage_interval
+            .unwrap_or((ticks_per_second * 60.0).round() as u32);
+        app.borrow_mut().watchdog = Watchdog::new(
+            options.on_progress,
+            options.progress_interval,
+            total_ticks,
+            started,
+            options.timeout,
+        );
 
-        parser.run_to_end()?;
-        
-        app.borrow_mut().tick_end(parser.context())?;
+        let run = std::panic::catch_unwind(AssertUnwindSafe(|| -> anyhow::Result<()> {
+            parser.run_to_end()?;
+            if truncated {
+                app.borrow_mut().data_end(parser.context());
+                Ok(())
+            } else {
+                app.borrow_mut().game_end(parser.context())
+            }
+        }));
+        let error = match run {
+            Ok(Ok(())) if truncated => Some(ParseError::new(&ParserError::ReplayEncodingError.into())),
+            Ok(Ok(())) => None,
+            Ok(Err(e)) => Some(ParseError::new(&e)),
+            Err(e) => Some(ParseError::panic(panic_message(&e))),
+        };
+        let match_info = {
+            let app = app.borrow();
+            let players = app.players.borrow();
+            MatchInfo::new(
+                parser.context(),
+                &replay_info,
+                &players,
+                &app.pauses,
+                app.ticks_per_second,
+            )
+        };
+        app.borrow_mut().match_info = match_info;
+        if let Some(mut error) = error {
+            if truncated && !matches!(error.kind, ErrorKind::Cancelled | ErrorKind::Timeout) {
+                error.kind = ErrorKind::Truncated;
+            }
+            app.borrow_mut().stop(parser.context(), error);
+        }
 
-        let x = Ok(app.borrow_mut().result.clone());
+        let x = Ok(std::mem::take(&mut *app.borrow_mut()));
         x
-    })
-    .map_err(|e| PyErr::new::<pyo3::exceptions::PyException, _>(format!("Panic while parsing\n{e:?}")))
-    .and_then(|x| {
-        x.map_err(|e: anyhow::Error| {
-            PyErr::new::<pyo3::exceptions::PyException, _>(format!("Error while parsing\n{e}"))
-        })
-    })
+    }))
+    .map_err(|e| ParseError::panic(panic_message(&e)))
+    .and_then(|x| x.map_err(|e: anyhow::Error| ParseError::new(&e)))
+}
+
+/// Parses ward events from replay `data`, which may be bzip2 or zstd
+/// compressed.
+pub fn parse(data: &[u8]) -> Result<Vec<WardRecord>, ParseError> {
+    parse_with(data, ParseOptions::default()).map(|result| result.wards)
+}
+
+/// Same as [`parse`] with `options`.
+pub fn parse_with(data: &[u8], options: ParseOptions) -> Result<ParseResult, ParseError> {
+    let allow_partial = options.allow_partial;
+    let data = ReplayData::Bytes(data)
+        .decompressed()
+        .map_err(|e| ParseError::new(&e.into()))?;
+    let app = run(&data, options)?;
+    match app.error {
+        Some(error) if !allow_partial => Err(error),
+        error => Ok(ParseResult {
+            match_info: app.match_info,
+            wards: app.result,
+            advantage: app.advantage,
+            opendota: app.opendota,
+            ticks_per_second: app.ticks_per_second,
+            warnings: app.warnings,
+            error,
+        }),
+    }
+}
+
+/// Match id from replay file name, which is `<match_id>_<salt>.dem` for
+/// replays downloaded from Valve.
+fn match_id_from_path(path: &Path) -> Option<u64> {
+    path.file_stem()?.to_str()?.split(['_', '.']).next()?.parse().ok()
+}
+
+/// Parses replay read from `path` into its match id and ward events. Match id
+/// is read from the replay, falling back to the leading number of the file
+/// name, and is `0` if neither is known.
+pub fn match_records(path: &Path, data: &[u8], options: ParseOptions) -> Result<(u64, Vec<WardRecord>), ParseError> {
+    let allow_partial = options.allow_partial;
+    let app = run(data, options)?;
+    match app.error {
+        Some(error) if !allow_partial => Err(error),
+        _ => Ok((
+            app.match_info
+                .match_id
+                .or_else(|| match_id_from_path(path))
+                .unwrap_or_default(),
+            app.result,
+        )),
+    }
+}
+
+/// Runs `parse_file` on replay data of every path on a pool of `workers`
+/// threads (one per CPU by default). Results are in input order.
+pub fn parse_files<T: Send>(
+    paths: &[PathBuf],
+    workers: Option<usize>,
+    parse_file: impl Fn(&Path, &[u8]) -> Result<T, ParseError> + Sync,
+) -> Result<Vec<Result<T, ParseError>>, rayon::ThreadPoolBuildError> {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(workers.unwrap_or_default())
+        .build()?;
+    Ok(pool.install(|| {
+        paths
+            .par_iter()
+            .map(|path| {
+                let data = ReplayData::open(path).map_err(|e| ParseError::io(&e, path))?;
+                parse_file(path, &data)
+            })
+            .collect()
+    }))
 }
 
-#[pymodule]
-#[pyo3(name = "d2wm_parser")]
-fn d2wm_parser(module: &Bound<'_, PyModule>) -> PyResult<()> {
-    module.add_class::<Output>()?;
-    module.add_function(wrap_pyfunction!(parse_replay, module)?)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pending(handle: u32, event: WardEvent) -> PendingEntry {
+        PendingEntry {
+            handle,
+            tick: 100,
+            event,
+            killer: None,
+            game_phase: "in_game",
+            position: Default::default(),
+            team_totals: Default::default(),
+            placer_economy: None,
+            destroyer_economy: None,
+        }
+    }
+
+    fn entry(ward_id: u32) -> WardEntry {
+        WardEntry {
+            ward_id,
+            hero_handle: 0,
+            player_slot: 0,
+            placed_tick: 50,
+            is_radiant: true,
+            is_observer: true,
+        }
+    }
+
+    #[test]
+    fn data_end_drops_epilogue_events() {
+        let mut app = App::default();
+        for handle in 1..=3 {
+            app.handle_to_entry.insert(handle, entry(handle));
+        }
+        app.pending_entries.extend([
+            pending(3, WardEvent::Placed),
+            pending(1, WardEvent::Expired),
+            pending(2, WardEvent::Killed("npc_dota_hero_axe".into())),
+        ]);
+        app.data_end(&Context::default());
+
+        let events = app
+            .pending_entries
+            .iter()
+            .map(|pending| (pending.handle, pending.event.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(events, [(3, WardEvent::Placed)]);
+        assert_eq!(app.warnings.len(), 1);
+        assert!(app.warnings[0].contains("3 wards still standing"));
+    }
 }
@@ -0,0 +1,163 @@
+use d2_stampede::prelude::*;
+use d2_stampede::proto::CDemoFileInfo;
+use d2_stampede_observers::players::*;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::pauses::Pauses;
+
+/// Player as listed in the replay.
+#[cfg_attr(feature = "python", pyclass(get_all))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlayerInfo {
+    /// Player id, `0..5` for Radiant and `5..10` for Dire.
+    pub slot: usize,
+    /// `2` for Radiant, `3` for Dire.
+    pub team: i32,
+    /// `0` for bots.
+    pub steam_id: u64,
+    /// Hero entity class, e.g. `CDOTA_Unit_Hero_ShadowShaman`.
+    pub hero_class: Option<String>,
+    /// Hero name, e.g. `npc_dota_hero_shadow_shaman`.
+    pub hero_name: Option<String>,
+    pub name: Option<String>,
+}
+
+/// Match context from replay file info, falling back to game rules for
+/// replays without one (e.g. truncated downloads). Fields neither has are
+/// `None`.
+#[cfg_attr(feature = "python", pyclass(get_all))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MatchInfo {
+    pub match_id: Option<u64>,
+    pub game_mode: Option<i32>,
+    pub lobby_type: Option<i32>,
+    pub league_id: Option<u32>,
+    /// Game build (patch) the replay was recorded on.
+    pub game_build: u32,
+    /// Winning team, `2` for Radiant, `3` for Dire.
+    pub winner: Option<i32>,
+    /// Unix time of the horn, `end_time` minus `duration` and the time the
+    /// game spent paused in between.
+    pub start_time: Option<u32>,
+    /// Unix time the match ended.
+    pub end_time: Option<u32>,
+    /// Game clock seconds from the horn to the ancient falling, pauses
+    /// excluded.
+    pub duration: Option<f32>,
+    pub players: Vec<PlayerInfo>,
+}
+
+/// Player name as shown in game, read from the player resource. `None` if
+/// the slot has no player or the name is empty.
+pub fn player_name(ctx: &Context, slot: usize) -> Option<String> {
+    let player_resource = ctx.entities().get_by_class_name("CDOTA_PlayerResource").ok()?;
+    try_property!(player_resource, String, "m_vecPlayerData.{slot:04}.m_iszPlayerName").filter(|name| !name.is_empty())
+}
+
+impl MatchInfo {
+    /// Collects match info once parsing has ended, from whatever state the
+    /// replay was left in.
+    pub fn new(
+        ctx: &Context,
+        replay_info: &CDemoFileInfo,
+        players: &Players,
+        pauses: &Pauses,
+        ticks_per_second: f32,
+    ) -> Self {
+        let dota = replay_info
+            .game_info
+            .as_ref()
+            .and_then(|game_info| game_info.dota.clone())
+            .unwrap_or_default();
+        let game_rules = ctx.entities().get_by_class_name("CDOTAGamerulesProxy").ok();
+
+        let game_clock = match (
+            game_rules.and_then(|game_rules| try_property!(game_rules, f32, "m_pGameRules.m_flGameStartTime")),
+            game_rules.and_then(|game_rules| try_property!(game_rules, f32, "m_pGameRules.m_flGameEndTime")),
+        ) {
+            (Some(start), Some(end)) if start > 0.0 && end > start => Some((start, end)),
+            _ => None,
+        };
+        let duration = game_clock.map(|(start, end)| end - start);
+        // Game clock stops during pauses, wall clock doesn't.
+        let wall_duration = game_clock.map(|(start, end)| {
+            let horn = pauses.raw_tick((start * ticks_per_second) as u32);
+            let game_end = pauses.raw_tick((end * ticks_per_second) as u32);
+            (game_end - horn) as f32 / ticks_per_second
+        });
+        let end_time = dota.end_time.filter(|&end_time| end_time != 0);
+
+        let players = if players.players.is_empty() {
+            dota.player_info
+                .iter()
+                .enumerate()
+                .map(|(slot, info)| PlayerInfo {
+                    slot,
+                    team: info.game_team(),
+                    steam_id: info.steamid(),
+                    hero_class: None,
+                    hero_name: info.hero_name.clone(),
+                    name: info.player_name.clone(),
+                })
+                .collect()
+        } else {
+            players
+                .players
+                .iter()
+                .enumerate()
+                .map(|(slot, player)| {
+                    let info = dota
+                        .player_info
+                        .get(slot)
+                        .filter(|info| info.steamid() == player.id)
+                        .or_else(|| {
+                            dota.player_info
+                                .iter()
+                                .find(|info| player.id != 0 && info.steamid() == player.id)
+                        });
+                    PlayerInfo {
+                        slot,
+                        team: player.team,
+                        steam_id: player.id,
+                        hero_class: Some(player.hero.to_string()),
+                        hero_name: info.and_then(|info| info.hero_name.clone()),
+                        name: info
+                            .and_then(|info| info.player_name.clone())
+                            .or_else(|| player_name(ctx, slot)),
+                    }
+                })
+                .collect()
+        };
+
+        MatchInfo {
+            match_id: dota
+                .match_id
+                .or_else(|| {
+                    game_rules.and_then(|game_rules| try_property!(game_rules, u64, "m_pGameRules.m_unMatchID64"))
+                })
+                .filter(|&match_id| match_id != 0),
+            game_mode: dota.game_mode.or_else(|| {
+                game_rules.and_then(|game_rules| try_property!(game_rules, i32, "m_pGameRules.m_iGameMode"))
+            }),
+            lobby_type: game_rules.and_then(|game_rules| try_property!(game_rules, i32, "m_pGameRules.m_lobbyType")),
+            league_id: dota.leagueid.or_else(|| {
+                game_rules.and_then(|game_rules| try_property!(game_rules, u32, "m_pGameRules.m_lobbyLeagueID"))
+            }),
+            game_build: ctx.game_build(),
+            winner: dota
+                .game_winner
+                .or_else(|| {
+                    game_rules.and_then(|game_rules| try_property!(game_rules, i32, "m_pGameRules.m_nGameWinner"))
+                })
+                .filter(|&winner| winner == 2 || winner == 3),
+            start_time: end_time
+                .zip(wall_duration)
+                .map(|(end_time, wall_duration)| end_time.saturating_sub(wall_duration as u32)),
+            end_time,
+            duration,
+            players,
+        }
+    }
+}
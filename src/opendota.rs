@@ -0,0 +1,119 @@
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Single entry of OpenDota's `obs_log`, `sen_log`, `obs_left_log` or
+/// `sen_left_log`.
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenDotaWard {
+    pub time: i32,
+    pub r#type: String,
+    pub key: String,
+    pub slot: i32,
+    pub player_slot: i32,
+    pub x: f32,
+    pub y: f32,
+    pub z: i32,
+    pub entityleft: bool,
+    pub ehandle: u32,
+    pub attackername: Option<String>,
+}
+
+#[cfg_attr(feature = "python", pyclass(get_all, set_all))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OpenDotaLogs {
+    pub obs_log: Vec<OpenDotaWard>,
+    pub sen_log: Vec<OpenDotaWard>,
+    pub obs_left_log: Vec<OpenDotaWard>,
+    pub sen_left_log: Vec<OpenDotaWard>,
+}
+
+impl OpenDotaLogs {
+    /// Adds an entry to the matching log. `cell` and `vec` are the raw
+    /// `CBodyComponent.m_cell*` and `CBodyComponent.m_vec*` values of the ward.
+    #[allow(clippy::too_many_arguments)]
+    pub fn push(
+        &mut self,
+        time: i32,
+        is_observer: bool,
+        left: bool,
+        slot: i32,
+        ehandle: u32,
+        attackername: Option<String>,
+        cell: (u16, u16, u16),
+        vec: (f32, f32),
+    ) {
+        let (log, r#type) = match (is_observer, left) {
+            (true, false) => (&mut self.obs_log, "obs_log"),
+            (false, false) => (&mut self.sen_log, "sen_log"),
+            (true, true) => (&mut self.obs_left_log, "obs_left_log"),
+            (false, true) => (&mut self.sen_left_log, "sen_left_log"),
+        };
+        log.push(OpenDotaWard {
+            time,
+            r#type: r#type.to_string(),
+            key: format!("[{},{}]", cell.0, cell.1),
+            slot,
+            player_slot: if slot < 5 { slot } else { slot + 123 },
+            x: precise_location(cell.0, vec.0),
+            y: precise_location(cell.1, vec.1),
+            z: cell.2 as i32,
+            entityleft: left,
+            ehandle,
+            attackername,
+        });
+    }
+}
+
+/// Position in OpenDota's 64-192 grid, i.e. cell plus sub-cell offset.
+fn precise_location(cell: u16, vec: f32) -> f32 {
+    (cell as f32 * 128.0 + vec) / 128.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push(logs: &mut OpenDotaLogs, is_observer: bool, left: bool, slot: i32) {
+        logs.push(600, is_observer, left, slot, 42, None, (100, 140, 130), (64.0, 32.0));
+    }
+
+    #[test]
+    fn entries_go_to_matching_log() {
+        let mut logs = OpenDotaLogs::default();
+        push(&mut logs, true, false, 0);
+        push(&mut logs, false, false, 1);
+        push(&mut logs, false, false, 2);
+        push(&mut logs, true, true, 3);
+        push(&mut logs, false, true, 4);
+        let types = |log: &[OpenDotaWard]| log.iter().map(|x| x.r#type.clone()).collect::<Vec<_>>();
+        assert_eq!(types(&logs.obs_log), ["obs_log"]);
+        assert_eq!(types(&logs.sen_log), ["sen_log", "sen_log"]);
+        assert_eq!(types(&logs.obs_left_log), ["obs_left_log"]);
+        assert_eq!(types(&logs.sen_left_log), ["sen_left_log"]);
+        assert!(!logs.sen_log[0].entityleft);
+        assert!(logs.sen_left_log[0].entityleft);
+    }
+
+    #[test]
+    fn player_slot_follows_opendota_convention() {
+        let mut logs = OpenDotaLogs::default();
+        for slot in [0, 4, 5, 9] {
+            push(&mut logs, true, false, slot);
+        }
+        let player_slots = logs.obs_log.iter().map(|x| (x.slot, x.player_slot)).collect::<Vec<_>>();
+        assert_eq!(player_slots, [(0, 0), (4, 4), (5, 128), (9, 132)]);
+    }
+
+    #[test]
+    fn position_uses_cell_grid() {
+        let mut logs = OpenDotaLogs::default();
+        push(&mut logs, true, false, 0);
+        let ward = &logs.obs_log[0];
+        assert_eq!(ward.key, "[100,140]");
+        assert_eq!((ward.x, ward.y, ward.z), (100.5, 140.25, 130));
+        assert_eq!(precise_location(64, 0.0), 64.0);
+        assert_eq!(precise_location(191, 127.0), 191.0 + 127.0 / 128.0);
+    }
+}
@@ -0,0 +1,101 @@
+use d2_stampede::prelude::*;
+
+/// Game pauses observed so far, as `[start, end)` intervals of raw net ticks.
+#[derive(Default)]
+pub struct Pauses {
+    pub intervals: Vec<(u32, u32)>,
+    current: Option<u32>,
+}
+
+impl Pauses {
+    pub fn update(&mut self, ctx: &Context) -> ObserverResult {
+        if let Ok(game_rules) = ctx.entities().get_by_class_name("CDOTAGamerulesProxy") {
+            let is_paused: bool = property!(game_rules, "m_pGameRules.m_bGamePaused");
+            match (is_paused, self.current) {
+                (true, None) => {
+                    let start: i32 = property!(game_rules, "m_pGameRules.m_nPauseStartTick");
+                    self.current = Some(start as u32);
+                }
+                (false, Some(start)) => {
+                    self.intervals.push((start, ctx.net_tick()));
+                    self.current = None;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Number of paused ticks between raw ticks `from` and `to`.
+    pub fn paused_ticks(&self, from: u32, to: u32) -> u32 {
+        self.intervals
+            .iter()
+            .copied()
+            .chain(self.current.map(|start| (start, u32::MAX)))
+            .map(|(start, end)| end.min(to).saturating_sub(start.max(from)))
+            .sum()
+    }
+
+    /// Converts a raw net tick into a game clock tick, which excludes pauses.
+    pub fn game_tick(&self, tick: u32) -> u32 {
+        tick - self.paused_ticks(0, tick)
+    }
+
+    /// Converts a game clock tick back into the first raw net tick the game
+    /// clock reached it at.
+    pub fn raw_tick(&self, game_tick: u32) -> u32 {
+        let mut tick = game_tick;
+        for &(start, end) in &self.intervals {
+            if start >= tick {
+                break;
+            }
+            tick += end - start;
+        }
+        tick
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pauses(intervals: &[(u32, u32)], current: Option<u32>) -> Pauses {
+        Pauses {
+            intervals: intervals.to_vec(),
+            current,
+        }
+    }
+
+    #[test]
+    fn paused_ticks_clips_intervals_to_range() {
+        let pauses = pauses(&[(100, 200), (300, 400)], None);
+        assert_eq!(pauses.paused_ticks(0, 1000), 200);
+        // Straddling `from`.
+        assert_eq!(pauses.paused_ticks(150, 250), 50);
+        // Straddling `to`.
+        assert_eq!(pauses.paused_ticks(250, 350), 50);
+        // Range inside a pause.
+        assert_eq!(pauses.paused_ticks(120, 180), 60);
+        assert_eq!(pauses.paused_ticks(200, 300), 0);
+    }
+
+    #[test]
+    fn paused_ticks_counts_ongoing_pause_up_to_to() {
+        let pauses = pauses(&[(100, 200)], Some(500));
+        assert_eq!(pauses.paused_ticks(0, 450), 100);
+        assert_eq!(pauses.paused_ticks(0, 600), 200);
+        assert_eq!(pauses.paused_ticks(550, 600), 50);
+        assert_eq!(pauses.game_tick(600), 400);
+    }
+
+    #[test]
+    fn raw_tick_inverts_game_tick() {
+        let pauses = pauses(&[(100, 200), (300, 400)], None);
+        for tick in [0, 50, 100, 250, 300, 1000] {
+            assert_eq!(pauses.raw_tick(pauses.game_tick(tick)), tick);
+        }
+        // Game clock reaches tick 100 when the first pause starts.
+        assert_eq!(pauses.raw_tick(100), 100);
+        assert_eq!(pauses.raw_tick(101), 201);
+    }
+}
@@ -0,0 +1,92 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Snapshot passed to the progress callback.
+pub struct Progress {
+    /// Raw net tick.
+    pub tick: u32,
+    /// Game clock time in seconds, unknown before the game has started.
+    pub game_time: Option<f32>,
+    /// Share of the replay consumed so far. The parser doesn't expose its read
+    /// position, so this is measured in ticks against the length recorded in
+    /// the replay header and is unknown for truncated replays.
+    pub fraction: Option<f32>,
+}
+
+/// Returns `false` to cancel parsing.
+pub type ProgressCallback = Box<dyn FnMut(&Progress) -> anyhow::Result<bool>>;
+
+/// Reason parsing was stopped on request.
+#[derive(Debug)]
+pub enum Interrupt {
+    Cancelled(String),
+    TimedOut(Duration),
+}
+
+impl fmt::Display for Interrupt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Interrupt::Cancelled(reason) => write!(f, "Parsing was cancelled: {reason}"),
+            Interrupt::TimedOut(timeout) => write!(f, "Parsing timed out after {:.1}s", timeout.as_secs_f32()),
+        }
+    }
+}
+
+impl std::error::Error for Interrupt {}
+
+/// Reports progress every `interval` ticks and enforces the timeout.
+#[derive(Default)]
+pub struct Watchdog {
+    callback: Option<ProgressCallback>,
+    interval: u32,
+    total_ticks: u32,
+    deadline: Option<(Instant, Duration)>,
+    next_tick: u32,
+}
+
+impl Watchdog {
+    /// `total_ticks` is the replay length from its header, zero if unknown.
+    /// `started` is when the timeout starts counting.
+    pub fn new(
+        callback: Option<ProgressCallback>,
+        interval: u32,
+        total_ticks: u32,
+        started: Instant,
+        timeout: Option<Duration>,
+    ) -> Self {
+        Watchdog {
+            callback,
+            interval,
+            total_ticks,
+            deadline: timeout.map(|timeout| (started, timeout)),
+            next_tick: 0,
+        }
+    }
+
+    pub fn check(&mut self, tick: u32, game_time: impl FnOnce() -> Option<f32>) -> anyhow::Result<()> {
+        if let Some((started, timeout)) = self.deadline {
+            if started.elapsed() > timeout {
+                return Err(Interrupt::TimedOut(timeout).into());
+            }
+        }
+        let Some(callback) = &mut self.callback else {
+            return Ok(());
+        };
+        if tick == u32::MAX || tick < self.next_tick {
+            return Ok(());
+        }
+        self.next_tick = tick.saturating_add(self.interval.max(1));
+        let progress = Progress {
+            tick,
+            game_time: game_time(),
+            fraction: Some(tick as f32 / self.total_ticks as f32)
+                .filter(|_| self.total_ticks > 0)
+                .map(|fraction| fraction.min(1.0)),
+        };
+        match callback(&progress) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(Interrupt::Cancelled("progress callback returned False".to_string()).into()),
+            Err(e) => Err(Interrupt::Cancelled(format!("progress callback raised {e}")).into()),
+        }
+    }
+}
@@ -0,0 +1,386 @@
+//! Python bindings, built with the `python` feature.
+
+use arrow::pyarrow::ToPyArrow;
+use arrow::record_batch::RecordBatch;
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyOSError, PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::stream::*;
+use crate::*;
+
+create_exception!(
+    d2wm_parser,
+    ReplayError,
+    PyException,
+    "Base class for replay parsing failures."
+);
+create_exception!(
+    d2wm_parser,
+    CorruptReplayError,
+    ReplayError,
+    "Replay data couldn't be decoded."
+);
+create_exception!(
+    d2wm_parser,
+    TruncatedReplayError,
+    ReplayError,
+    "Replay ends before the end of the game."
+);
+create_exception!(
+    d2wm_parser,
+    UnsupportedReplayVersionError,
+    ReplayError,
+    "Replay format or entity schema isn't supported."
+);
+create_exception!(
+    d2wm_parser,
+    MissingEntityError,
+    ReplayError,
+    "Entity required for parsing doesn't exist."
+);
+create_exception!(d2wm_parser, InternalPanic, ReplayError, "Parser panicked.");
+create_exception!(
+    d2wm_parser,
+    ParseCancelledError,
+    ReplayError,
+    "Parsing was cancelled by the progress callback."
+);
+create_exception!(
+    d2wm_parser,
+    ParseTimeoutError,
+    ReplayError,
+    "Parsing took longer than `timeout_seconds`."
+);
+
+impl ParseError {
+    /// Converts into the matching [`ReplayError`] subclass, or `OSError` for
+    /// [`ErrorKind::Io`]. Exception carries
+    /// `message`, `tick`, `game_time` and `entity_class` attributes.
+    pub fn into_py_err(self, py: Python) -> PyErr {
+        let message = match self.tick {
+            Some(tick) => format!("Error while parsing at tick {tick}\n{}", self.message),
+            None => format!("Error while parsing\n{}", self.message),
+        };
+        let err = match self.kind {
+            ErrorKind::Corrupt => CorruptReplayError::new_err(message),
+            ErrorKind::Truncated => TruncatedReplayError::new_err(message),
+            ErrorKind::UnsupportedVersion => UnsupportedReplayVersionError::new_err(message),
+            ErrorKind::MissingEntity => MissingEntityError::new_err(message),
+            ErrorKind::Panic => InternalPanic::new_err(message),
+            ErrorKind::Io => PyOSError::new_err(self.message.clone()),
+            ErrorKind::Cancelled => ParseCancelledError::new_err(message),
+            ErrorKind::Timeout => ParseTimeoutError::new_err(message),
+        };
+        let value = err.value_bound(py);
+        let attributes = value
+            .setattr("message", self.message)
+            .and_then(|_| value.setattr("tick", self.tick))
+            .and_then(|_| value.setattr("game_time", self.game_time))
+            .and_then(|_| value.setattr("entity_class", self.entity_class));
+        match attributes {
+            Ok(()) => err,
+            Err(e) => e,
+        }
+    }
+}
+
+fn runtime_err(e: impl ToString) -> PyErr {
+    PyRuntimeError::new_err(e.to_string())
+}
+
+/// `pyarrow.Table` holding `batch`, passed through Arrow C data interface
+/// without creating per row Python objects.
+fn pyarrow_table(py: Python, batch: arrow::error::Result<RecordBatch>) -> PyResult<PyObject> {
+    let batch = batch.map_err(runtime_err)?.to_pyarrow(py)?;
+    let table = py
+        .import_bound("pyarrow")?
+        .getattr("Table")?
+        .call_method1("from_batches", (vec![batch],))?;
+    Ok(table.unbind())
+}
+
+fn polars_frame(py: Python, table: PyObject) -> PyResult<PyObject> {
+    Ok(py
+        .import_bound("polars")?
+        .call_method1("from_arrow", (table,))?
+        .unbind())
+}
+
+#[pymethods]
+impl ParseResult {
+    /// Ward events as `pyarrow.Table`.
+    fn to_arrow(&self, py: Python) -> PyResult<PyObject> {
+        pyarrow_table(py, record_batch(&self.wards))
+    }
+
+    /// Ward events as `polars.DataFrame`.
+    fn to_polars(&self, py: Python) -> PyResult<PyObject> {
+        polars_frame(py, self.to_arrow(py)?)
+    }
+
+    /// Ward events as `pandas.DataFrame`.
+    fn to_pandas(&self, py: Python) -> PyResult<PyObject> {
+        self.to_arrow(py)?.call_method0(py, "to_pandas")
+    }
+
+    /// Advantage samples as `pyarrow.Table`.
+    fn advantage_to_arrow(&self, py: Python) -> PyResult<PyObject> {
+        pyarrow_table(py, advantage_batch(&self.advantage))
+    }
+
+    /// Advantage samples as `polars.DataFrame`.
+    fn advantage_to_polars(&self, py: Python) -> PyResult<PyObject> {
+        polars_frame(py, self.advantage_to_arrow(py)?)
+    }
+
+    /// Advantage samples as `pandas.DataFrame`.
+    fn advantage_to_pandas(&self, py: Python) -> PyResult<PyObject> {
+        self.advantage_to_arrow(py)?.call_method0(py, "to_pandas")
+    }
+}
+
+/// Wraps Python `callback(tick, game_time, fraction)`. Any return value other
+/// than `False` (including `None`) continues parsing.
+fn py_progress_callback(callback: PyObject) -> ProgressCallback {
+    Box::new(move |progress| {
+        Python::with_gil(|py| {
+            let result = callback.call1(py, (progress.tick, progress.game_time, progress.fraction))?;
+            Ok(!result.bind(py).is(&*pyo3::types::PyBool::new_bound(py, false)))
+        })
+    })
+}
+
+/// Parse options shared by [`parse_replay`] and [`parse_replay_file`].
+fn py_parse_options(
+    allow_partial: bool,
+    progress: Option<PyObject>,
+    progress_interval: u32,
+    timeout_seconds: Option<f64>,
+    advantage_interval: Option<u32>,
+    opendota: bool,
+) -> ParseOptions {
+    ParseOptions {
+        allow_partial,
+        on_progress: progress.map(py_progress_callback),
+        progress_interval,
+        timeout: timeout_seconds.map(Duration::from_secs_f64),
+        advantage_interval,
+        opendota,
+        ..Default::default()
+    }
+}
+
+/// Parses ward events from replay. `data` is `bytes` or any object supporting
+/// buffer protocol, which is read without copying unless it's bzip2 or zstd
+/// compressed. If `allow_partial` is set,
+/// a replay that fails midway (e.g. truncated download) returns everything
+/// parsed up to that point with `error` describing where it stopped, instead
+/// of raising.
+///
+/// `progress(tick, game_time, fraction)` is called every `progress_interval`
+/// ticks, returning `False` or raising cancels parsing with
+/// `ParseCancelledError`. Parsing running longer than `timeout_seconds` stops
+/// with `ParseTimeoutError`.
+///
+/// Team net worth and experience are sampled into `advantage` every
+/// `advantage_interval` game ticks, one game minute by default. If `opendota`
+/// is set, `opendota` holds ward logs in OpenDota's parsed match format.
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+#[pyo3(signature = (data, allow_partial = false, progress = None, progress_interval = 1800, timeout_seconds = None, advantage_interval = None, opendota = false))]
+pub fn parse_replay(
+    py: Python,
+    data: &Bound<PyAny>,
+    allow_partial: bool,
+    progress: Option<PyObject>,
+    progress_interval: u32,
+    timeout_seconds: Option<f64>,
+    advantage_interval: Option<u32>,
+    opendota: bool,
+) -> PyResult<ParseResult> {
+    let data = ReplayData::from_py(data)?;
+    py.allow_threads(|| {
+        let options = py_parse_options(
+            allow_partial,
+            progress,
+            progress_interval,
+            timeout_seconds,
+            advantage_interval,
+            opendota,
+        );
+        parse_with(&data, options)
+    })
+    .map_err(|e| e.into_py_err(py))
+}
+
+/// Same as [`parse_replay`] but memory maps replay from `path`.
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+#[pyo3(signature = (path, allow_partial = false, progress = None, progress_interval = 1800, timeout_seconds = None, advantage_interval = None, opendota = false))]
+pub fn parse_replay_file(
+    py: Python,
+    path: PathBuf,
+    allow_partial: bool,
+    progress: Option<PyObject>,
+    progress_interval: u32,
+    timeout_seconds: Option<f64>,
+    advantage_interval: Option<u32>,
+    opendota: bool,
+) -> PyResult<ParseResult> {
+    py.allow_threads(|| {
+        let data = ReplayData::open(&path).map_err(|e| ParseError::io(&e, &path))?;
+        let options = py_parse_options(
+            allow_partial,
+            progress,
+            progress_interval,
+            timeout_seconds,
+            advantage_interval,
+            opendota,
+        );
+        parse_with(&data, options)
+    })
+    .map_err(|e| e.into_py_err(py))
+}
+
+/// Parses replays at `paths` on a pool of `workers` threads (one per CPU by
+/// default) without holding the GIL. Returns a list in input order holding
+/// either [`ParseResult`] or the exception parsing that replay raised.
+#[pyfunction]
+#[pyo3(signature = (paths, workers = None, allow_partial = false))]
+pub fn parse_replays(
+    py: Python,
+    paths: Vec<PathBuf>,
+    workers: Option<usize>,
+    allow_partial: bool,
+) -> PyResult<Vec<PyObject>> {
+    let results = py
+        .allow_threads(|| {
+            parse_files(&paths, workers, |_, data| {
+                let options = ParseOptions {
+                    allow_partial,
+                    ..Default::default()
+                };
+                parse_with(data, options)
+            })
+        })
+        .map_err(runtime_err)?;
+    Ok(results
+        .into_iter()
+        .map(|result| match result {
+            Ok(result) => result.into_py(py),
+            Err(e) => e.into_py_err(py).into_value(py).into_any(),
+        })
+        .collect())
+}
+
+/// Parses replays at `paths` in parallel like [`parse_replays`] and
+/// writes all ward events into a single Parquet or CSV file at `output`, with
+/// a leading `match_id` column. `format` is `parquet` or `csv`, taken from
+/// `output` extension by default. In CSV, struct columns such as `killer` are
+/// flattened into `<column>_<field>` columns.
+///
+/// `output` is created before parsing starts and each replay is written as
+/// soon as it's parsed, so rows are grouped by replay in the order replays
+/// finish. Match id is read from the replay, falling back to the leading
+/// number of the file name. Returns a list in input order holding `None` for
+/// replays that were written or the exception parsing that replay raised,
+/// which leaves it out of the output.
+#[pyfunction]
+#[pyo3(signature = (paths, output, format = None, workers = None, allow_partial = false))]
+pub fn export_replays(
+    py: Python,
+    paths: Vec<PathBuf>,
+    output: PathBuf,
+    format: Option<&str>,
+    workers: Option<usize>,
+    allow_partial: bool,
+) -> PyResult<Vec<PyObject>> {
+    let format = match format {
+        Some(format) => ExportFormat::from_name(format),
+        None => ExportFormat::from_path(&output),
+    }
+    .ok_or_else(|| PyValueError::new_err("Unknown export format, expected 'parquet' or 'csv'"))?;
+    let file = BufWriter::new(File::create(&output)?);
+    let writer = Mutex::new(RecordsWriter::new(file, format).map_err(runtime_err)?);
+    // First write failure, which skips every replay after it.
+    let write_error = Mutex::new(None);
+    let results = py
+        .allow_threads(|| {
+            parse_files(&paths, workers, |path, data| {
+                if write_error.lock().unwrap().is_some() {
+                    return Ok(());
+                }
+                let options = ParseOptions {
+                    allow_partial,
+                    ..Default::default()
+                };
+                let (match_id, wards) = match_records(path, data, options)?;
+                if let Err(e) = writer.lock().unwrap().write(match_id, &wards) {
+                    write_error.lock().unwrap().get_or_insert(e);
+                }
+                Ok(())
+            })
+        })
+        .map_err(runtime_err)?;
+    if let Some(e) = write_error.into_inner().unwrap() {
+        return Err(runtime_err(format!("{}: {e}", output.display())));
+    }
+    py.allow_threads(|| writer.into_inner().unwrap().finish())
+        .map_err(runtime_err)?;
+    Ok(results
+        .into_iter()
+        .map(|result| match result {
+            Ok(()) => py.None(),
+            Err(e) => e.into_py_err(py).into_value(py).into_any(),
+        })
+        .collect())
+}
+
+#[pymodule]
+#[pyo3(name = "d2wm_parser")]
+fn d2wm_parser(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<WardRecord>()?;
+    module.add_class::<ParseResult>()?;
+    module.add_class::<MatchInfo>()?;
+    module.add_class::<PlayerInfo>()?;
+    module.add_class::<ParseError>()?;
+    module.add_class::<ErrorKind>()?;
+    module.add("ReplayError", module.py().get_type_bound::<ReplayError>())?;
+    module.add("CorruptReplayError", module.py().get_type_bound::<CorruptReplayError>())?;
+    module.add(
+        "TruncatedReplayError",
+        module.py().get_type_bound::<TruncatedReplayError>(),
+    )?;
+    module.add(
+        "UnsupportedReplayVersionError",
+        module.py().get_type_bound::<UnsupportedReplayVersionError>(),
+    )?;
+    module.add("MissingEntityError", module.py().get_type_bound::<MissingEntityError>())?;
+    module.add("InternalPanic", module.py().get_type_bound::<InternalPanic>())?;
+    module.add(
+        "ParseCancelledError",
+        module.py().get_type_bound::<ParseCancelledError>(),
+    )?;
+    module.add("ParseTimeoutError", module.py().get_type_bound::<ParseTimeoutError>())?;
+    module.add_class::<Killer>()?;
+    module.add_class::<Economy>()?;
+    module.add_class::<AdvantageSample>()?;
+    module.add_class::<WardEvents>()?;
+    module.add_class::<OpenDotaWard>()?;
+    module.add_class::<OpenDotaLogs>()?;
+    module.add_function(wrap_pyfunction!(parse_replay, module)?)?;
+    module.add_function(wrap_pyfunction!(parse_replay_file, module)?)?;
+    module.add_function(wrap_pyfunction!(parse_replays, module)?)?;
+    module.add_function(wrap_pyfunction!(iter_ward_events, module)?)?;
+    module.add_function(wrap_pyfunction!(export_replays, module)?)?;
+    module.add_function(wrap_pyfunction!(cell_to_world, module)?)?;
+    module.add_function(wrap_pyfunction!(world_to_cell, module)?)?;
+    module.add_function(wrap_pyfunction!(world_to_minimap, module)?)?;
+    module.add_function(wrap_pyfunction!(minimap_to_world, module)?)
+}
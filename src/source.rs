@@ -0,0 +1,131 @@
+use memmap2::Mmap;
+#[cfg(feature = "python")]
+use pyo3::{buffer::PyBuffer, exceptions::PyValueError, prelude::*, types::PyBytes};
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
+use std::ops::Deref;
+use std::path::Path;
+
+enum Compression {
+    Bzip2,
+    Zstd,
+}
+
+impl Compression {
+    fn detect(magic: &[u8]) -> Option<Self> {
+        if magic.starts_with(b"BZh") {
+            Some(Compression::Bzip2)
+        } else if magic.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(Compression::Zstd)
+        } else {
+            None
+        }
+    }
+
+    fn decode<R: Read>(&self, reader: R) -> std::io::Result<Vec<u8>> {
+        let mut data = Vec::new();
+        match self {
+            Compression::Bzip2 => bzip2::read::MultiBzDecoder::new(reader).read_to_end(&mut data),
+            Compression::Zstd => zstd::Decoder::new(reader)?.read_to_end(&mut data),
+        }
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        Ok(data)
+    }
+}
+
+/// Replay bytes, either borrowed (from a Python object) or memory mapped from
+/// file. Dereferences to the whole replay without copying it. Compressed
+/// `.dem.bz2` and `.dem.zst` replays are detected by magic bytes and
+/// decompressed into memory.
+pub enum ReplayData<'a> {
+    Bytes(&'a [u8]),
+    /// Any object supporting buffer protocol (`memoryview`, `mmap`, numpy
+    /// `uint8` arrays...). Must not be modified while parsing.
+    #[cfg(feature = "python")]
+    Buffer(PyBuffer<u8>),
+    Mapped(Mmap),
+    Decompressed(Vec<u8>),
+}
+
+impl<'a> ReplayData<'a> {
+    #[cfg(feature = "python")]
+    pub fn from_py(data: &'a Bound<'_, PyAny>) -> PyResult<Self> {
+        if let Ok(bytes) = data.downcast::<PyBytes>() {
+            return Ok(ReplayData::Bytes(bytes.as_bytes()));
+        }
+        let buffer = PyBuffer::<u8>::get_bound(data)?;
+        if !buffer.is_c_contiguous() {
+            return Err(PyValueError::new_err("Replay buffer must be C-contiguous"));
+        }
+        Ok(ReplayData::Buffer(buffer))
+    }
+
+    pub fn open(path: &Path) -> std::io::Result<Self> {
+        let mut file = File::open(path)?;
+        let mut magic = Vec::with_capacity(4);
+        (&mut file).take(4).read_to_end(&mut magic)?;
+        if let Some(compression) = Compression::detect(&magic) {
+            file.rewind()?;
+            return Ok(ReplayData::Decompressed(compression.decode(BufReader::new(file))?));
+        }
+        // Safety: replay files aren't expected to be modified while parsing.
+        Ok(ReplayData::Mapped(unsafe { Mmap::map(&file)? }))
+    }
+
+    /// Decompresses replay if it's compressed, otherwise returns it as is.
+    pub fn decompressed(self) -> std::io::Result<Self> {
+        match Compression::detect(&self) {
+            Some(compression) => Ok(ReplayData::Decompressed(compression.decode(&*self)?)),
+            None => Ok(self),
+        }
+    }
+}
+
+impl Deref for ReplayData<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            ReplayData::Bytes(bytes) => bytes,
+            // Safety: buffer is C-contiguous and kept alive by `PyBuffer`.
+            #[cfg(feature = "python")]
+            ReplayData::Buffer(buffer) => unsafe {
+                std::slice::from_raw_parts(buffer.buf_ptr() as *const u8, buffer.len_bytes())
+            },
+            ReplayData::Mapped(mmap) => mmap,
+            ReplayData::Decompressed(data) => data,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REPLAY: &[u8] = b"PBDEMS2\0\x10\0\0\0\0\0\0\0replay data";
+
+    fn bzip2(data: &[u8]) -> Vec<u8> {
+        let mut compressed = Vec::new();
+        bzip2::read::BzEncoder::new(data, bzip2::Compression::default())
+            .read_to_end(&mut compressed)
+            .unwrap();
+        compressed
+    }
+
+    #[test]
+    fn compressed_replays_are_decompressed() {
+        for compressed in [bzip2(REPLAY), zstd::encode_all(REPLAY, 0).unwrap()] {
+            assert!(Compression::detect(&compressed).is_some());
+            let data = ReplayData::Bytes(&compressed).decompressed().unwrap();
+            assert!(matches!(data, ReplayData::Decompressed(_)));
+            assert_eq!(&*data, REPLAY);
+        }
+    }
+
+    #[test]
+    fn uncompressed_replays_are_borrowed() {
+        assert!(Compression::detect(REPLAY).is_none());
+        let data = ReplayData::Bytes(REPLAY).decompressed().unwrap();
+        assert!(matches!(data, ReplayData::Bytes(bytes) if std::ptr::eq(bytes, REPLAY)));
+    }
+}
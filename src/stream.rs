@@ -0,0 +1,60 @@
+use anyhow::anyhow;
+use pyo3::prelude::*;
+use std::path::PathBuf;
+use std::sync::mpsc::{sync_channel, Receiver};
+
+use crate::error::*;
+use crate::source::*;
+use crate::{run, ParseOptions, WardRecord};
+
+/// Number of resolved ward events buffered ahead of the consumer.
+const EVENTS_BUFFER: usize = 1024;
+
+/// Iterator over ward events of a replay parsed on a background thread.
+/// Dropping it stops the parse.
+#[pyclass]
+pub struct WardEvents {
+    messages: Receiver<Result<WardRecord, ParseError>>,
+}
+
+#[pymethods]
+impl WardEvents {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python) -> PyResult<Option<WardRecord>> {
+        let messages = &mut self.messages;
+        match py.allow_threads(move || messages.recv()) {
+            Ok(Ok(output)) => Ok(Some(output)),
+            Ok(Err(e)) => Err(e.into_py_err(py)),
+            Err(_) => Ok(None),
+        }
+    }
+}
+
+/// Parses replay at `path` yielding each ward event as soon as it's resolved.
+/// Raises once parsing fails, after yielding everything resolved before.
+#[pyfunction]
+pub fn iter_ward_events(path: PathBuf) -> WardEvents {
+    let (messages, receiver) = sync_channel(EVENTS_BUFFER);
+    std::thread::spawn(move || {
+        let events = messages.clone();
+        let options = ParseOptions {
+            on_event: Some(Box::new(move |output| {
+                events
+                    .send(Ok(output))
+                    .map_err(|_| anyhow!("Ward events iterator was dropped"))
+            })),
+            ..Default::default()
+        };
+        let error = ReplayData::open(&path)
+            .map_err(|e| ParseError::io(&e, &path))
+            .and_then(|data| run(&data, options))
+            .map_or_else(Some, |app| app.error);
+        if let Some(error) = error {
+            let _ = messages.send(Err(error));
+        }
+    });
+    WardEvents { messages: receiver }
+}